@@ -3,7 +3,7 @@ use std::process::Command;
 fn main() {
     // Try to obtain the current git commit (short). Fall back to "unknown".
     let git_commit = Command::new("git")
-        .args(&["rev-parse", "--short", "HEAD"])
+        .args(["rev-parse", "--short", "HEAD"])
         .output()
         .ok()
         .and_then(|o| if o.status.success() { String::from_utf8(o.stdout).ok() } else { None })
@@ -0,0 +1,248 @@
+// === src/codebook.rs ===
+
+//! Codebook (data dictionary): one entry per answer key with the question text, the
+//! options, its missing-value codes and where the variable is used, rendered as
+//! Markdown, HTML or CSV.
+
+use crate::export::{choice_items, document_title, draws, scales};
+use crate::items::common::escape_html;
+use crate::table::row;
+use crate::transcompiler::Entry;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum CodebookFormat {
+    #[default]
+    Markdown,
+    Html,
+    Csv,
+}
+
+impl CodebookFormat {
+    /// Format implied by an output file name, if any.
+    pub fn from_path(path: &str) -> Option<Self> {
+        let lower = path.to_ascii_lowercase();
+        if lower.ends_with(".html") || lower.ends_with(".htm") {
+            Some(CodebookFormat::Html)
+        } else if lower.ends_with(".csv") {
+            Some(CodebookFormat::Csv)
+        } else if lower.ends_with(".md") {
+            Some(CodebookFormat::Markdown)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Variable {
+    pub name: String,
+    pub question: String,
+    pub item_type: &'static str,
+    /// 1-based page number and page title
+    pub page: (usize, String),
+    pub required: bool,
+    /// `(value, label)` pairs in option order
+    pub options: Vec<(String, String)>,
+    /// Missing-value codes as `(value, label, kind)`
+    pub missing: Vec<(String, String, &'static str)>,
+    /// Scales the variable belongs to, `name` or `name (reversed)`
+    pub scales: Vec<String>,
+}
+
+/// Collect the codebook entries of a questionnaire in questionnaire order.
+pub fn variables(ast: &[Entry]) -> Vec<Variable> {
+    let page_titles: Vec<&str> = ast
+        .iter()
+        .filter_map(|e| match e {
+            Entry::Page { title, .. } => Some(title.as_str()),
+            _ => None,
+        })
+        .collect();
+    let declared = scales(ast);
+    let draws = draws(ast);
+
+    let mut vars = Vec::new();
+    for (page_idx, key, c) in choice_items(ast) {
+        let page = (page_idx + 1, page_titles[page_idx].to_string());
+        // the pick of a draw, listed before the questions it picks from
+        if let Some((_, d)) = draws.iter().find(|(_, d)| d.item_keys().first() == Some(&key.as_str())) {
+            let filter = d.filter.as_ref().map(|f| format!(" where {}", f)).unwrap_or_default();
+            vars.push(Variable {
+                name: d.key.clone(),
+                question: format!("{} of {} questions drawn from bank {}{}", d.count, d.items.len(), d.bank, filter),
+                item_type: "draw",
+                page: page.clone(),
+                required: false,
+                options: Vec::new(),
+                missing: Vec::new(),
+                scales: Vec::new(),
+            });
+        }
+        let other = c.other_keys(&key);
+        vars.push(Variable {
+            scales: declared
+                .iter()
+                .flat_map(|s| s.items.iter().filter(|i| i.key == key).map(move |i| (s, i)))
+                .map(|(s, i)| if i.reversed { format!("{} (reversed)", s.name) } else { s.name.clone() })
+                .collect(),
+            name: key,
+            question: c.question.clone(),
+            item_type: if c.is_multiple() { "multiple choice" } else { "single choice" },
+            page: page.clone(),
+            required: c.is_required(),
+            options: c.options.iter().map(|(label, value)| (value.to_string(), label.clone())).collect(),
+            missing: c
+                .missing
+                .iter()
+                .filter_map(|m| Some((m.value.as_ref()?.to_string(), m.label().to_string(), m.kind.name())))
+                .collect(),
+        });
+        // "other, please specify" text, answered when its option is chosen
+        for (i, other_key) in other {
+            vars.push(Variable {
+                name: other_key,
+                question: format!("{}: {}", c.question, c.options[i].0),
+                item_type: "text",
+                page: page.clone(),
+                required: false,
+                options: Vec::new(),
+                missing: Vec::new(),
+                scales: Vec::new(),
+            });
+        }
+    }
+    vars
+}
+
+pub fn render(ast: &[Entry], format: CodebookFormat) -> String {
+    let vars = variables(ast);
+    let title = document_title(ast);
+    match format {
+        CodebookFormat::Markdown => to_markdown(&title, &vars),
+        CodebookFormat::Html => to_html(&title, &vars),
+        CodebookFormat::Csv => to_csv(&vars),
+    }
+}
+
+fn yes_no(b: bool) -> &'static str {
+    if b { "yes" } else { "no" }
+}
+
+/// Markdown table cells cannot contain pipes or line breaks.
+fn md_cell(s: &str) -> String {
+    s.replace('|', "\\|").replace('\n', " ")
+}
+
+fn to_markdown(title: &str, vars: &[Variable]) -> String {
+    let mut out = format!("# Codebook: {}\n\n", title);
+    out.push_str("| Variable | Question | Type | Page | Required | Values | Scales |\n");
+    out.push_str("|---|---|---|---|---|---|---|\n");
+    for v in vars {
+        let mut values: Vec<String> =
+            v.options.iter().map(|(value, label)| format!("`{}` = {}", md_cell(value), md_cell(label))).collect();
+        values.extend(
+            v.missing.iter().map(|(value, label, kind)| format!("`{}` = {} (missing: {})", md_cell(value), md_cell(label), kind)),
+        );
+        out.push_str(&format!(
+            "| `{}` | {} | {} | {} ({}) | {} | {} | {} |\n",
+            md_cell(&v.name),
+            md_cell(&v.question),
+            v.item_type,
+            v.page.0,
+            md_cell(&v.page.1),
+            yes_no(v.required),
+            values.join("<br>"),
+            md_cell(&v.scales.join(", "))
+        ));
+    }
+    out
+}
+
+fn to_html(title: &str, vars: &[Variable]) -> String {
+    let mut out = String::new();
+    out.push_str("<!doctype html>\n<html><head><meta charset=\"utf-8\">");
+    out.push_str(&format!("<title>Codebook: {}</title>\n", escape_html(title)));
+    out.push_str("<style>body{font-family: system-ui, -apple-system, Roboto, 'Segoe UI', Arial; padding:20px; max-width:1100px; margin:auto;}");
+    out.push_str("table{border-collapse:collapse;width:100%}th,td{border:1px solid #ddd;padding:6px;text-align:left;vertical-align:top}");
+    out.push_str("th{background:#f8f8f8}code{font-size:.95em}</style>\n</head><body>\n");
+    out.push_str(&format!("<h1>Codebook: {}</h1>\n", escape_html(title)));
+    out.push_str("<table>\n<tr><th>Variable</th><th>Question</th><th>Type</th><th>Page</th><th>Required</th><th>Values</th><th>Scales</th></tr>\n");
+    for v in vars {
+        let mut values: Vec<String> = v
+            .options
+            .iter()
+            .map(|(value, label)| format!("<code>{}</code> = {}", escape_html(value), escape_html(label)))
+            .collect();
+        values.extend(v.missing.iter().map(|(value, label, kind)| {
+            format!("<code>{}</code> = {} <em>(missing: {})</em>", escape_html(value), escape_html(label), kind)
+        }));
+        out.push_str(&format!(
+            "<tr><td><code>{}</code></td><td>{}</td><td>{}</td><td>{} ({})</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            escape_html(&v.name),
+            escape_html(&v.question),
+            v.item_type,
+            v.page.0,
+            escape_html(&v.page.1),
+            yes_no(v.required),
+            values.join("<br>"),
+            escape_html(&v.scales.join(", "))
+        ));
+    }
+    out.push_str("</table>\n</body></html>\n");
+    out
+}
+
+/// Long format: one row per option and missing-value code, so value labels can be
+/// joined onto the data; `missing` names the kind of a code row.
+fn to_csv(vars: &[Variable]) -> String {
+    let mut lines = vec![row(
+        &["variable", "question", "type", "page", "page_title", "required", "scales", "value", "label", "missing"],
+        ',',
+    )];
+    for v in vars {
+        let page = v.page.0.to_string();
+        let scales = v.scales.join("; ");
+        let base = [v.name.as_str(), &v.question, v.item_type, &page, &v.page.1, yes_no(v.required), &scales];
+        if v.options.is_empty() {
+            lines.push(row(&[&base[..], &["", "", ""]].concat(), ','));
+        }
+        for (value, label) in &v.options {
+            lines.push(row(&[&base[..], &[value.as_str(), label.as_str(), ""]].concat(), ','));
+        }
+        for (value, label, kind) in &v.missing {
+            lines.push(row(&[&base[..], &[value.as_str(), label.as_str(), kind]].concat(), ','));
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::{Choose, Scale};
+    use crate::transcompiler::Question;
+
+    #[test]
+    fn lists_options_required_and_scales() {
+        let ast = vec![
+            Entry::Scale(Scale::parse("neuro", "n1 -n2")),
+            Entry::Page {
+                title: "Mood".to_string(),
+                time_limit: None,
+                content: vec![
+                    Question::Choose(Box::new(Choose::parse("Often worried?\nYes >> 1\nNo >> 0\n.addons [\nrequired\n]", Some("n1".into())))),
+                    Question::Choose(Box::new(Choose::parse("Calm, mostly?\nYes >> 1\nNo >> 0\n.missing refused >> -99", Some("n2".into())))),
+                ],
+            },
+        ];
+        let vars = variables(&ast);
+        assert_eq!(vars.len(), 2);
+        assert!(vars[0].required && !vars[1].required);
+        assert_eq!(vars[0].scales, vec!["neuro"]);
+        assert_eq!(vars[1].scales, vec!["neuro (reversed)"]);
+
+        let csv = to_csv(&vars);
+        assert!(csv.contains("n2,\"Calm, mostly?\",single choice,1,Mood,no,neuro (reversed),1,Yes,\n"));
+        assert!(csv.contains(",neuro (reversed),-99,Prefer not to answer,refused\n"));
+    }
+}
@@ -0,0 +1,173 @@
+// === src/commands/build.rs ===
+
+use std::io;
+use std::path::Path;
+use std::process;
+use std::time::Instant;
+
+use serde_json::json;
+
+use crate::commands::{compile_or_exit, require_input_file};
+use crate::convert::{self, BuildOptions};
+use crate::items::qrcode::print_qrcode;
+use crate::lint::{self, Level, LintConfig};
+use crate::messages::Reporter;
+use crate::transcompiler::{self, Entry};
+use crate::watch::watch_sources;
+
+pub const DOCS_URL: &str = "https://eypozax.github.io/docs/SQE/";
+
+#[derive(clap::Args, Debug)]
+pub struct BuildArgs {
+    /// Input .sqe file to compile
+    #[arg(value_name = "FILE")]
+    pub input: String,
+
+    /// Output directory
+    #[arg(long, short, value_name = "DIR", default_value = "out")]
+    pub output: String,
+
+    /// Print the parsed AST (debug representation)
+    #[arg(long)]
+    pub print_ast: bool,
+
+    /// Print a terminal QR code for URL (defaults to the SQE docs)
+    #[arg(long, value_name = "URL", num_args = 0..=1, default_missing_value = DOCS_URL)]
+    pub qr: Option<String>,
+
+    /// Rebuild whenever the input or any imported file changes
+    #[arg(long)]
+    pub watch: bool,
+
+    /// Leave quiz answers, points and feedback out of the page (grade with `score`)
+    #[arg(long)]
+    pub hide_answers: bool,
+}
+
+impl BuildArgs {
+    fn options(&self) -> BuildOptions {
+        BuildOptions { hide_answers: self.hide_answers }
+    }
+}
+
+pub fn run(args: &BuildArgs, out: &Reporter) -> io::Result<()> {
+    let input_path = require_input_file(&args.input, out);
+    if args.watch {
+        return watch(input_path, args, out);
+    }
+    let out_dir = &args.output;
+
+    let ast = compile_or_exit(input_path, out);
+    if args.print_ast {
+        println!("Parsed AST:\n{:#?}", ast);
+    }
+
+    if let Err(e) = convert::build_pages(&ast, out_dir, &args.options()) {
+        out.error(&format!("Failed to write output to {}: {}", out_dir, e));
+        process::exit(1);
+    }
+
+    if out.is_json() {
+        if !out.quiet {
+            out.emit(json!({
+                "event": "build",
+                "status": "ok",
+                "input": args.input,
+                "output": out_dir,
+            }));
+        }
+    } else {
+        out.info(&format!("Wrote HTML files to {} (open {}/index.html)", out_dir, out_dir));
+    }
+
+    // Package & build metadata embedded at compile time
+    out.detail(&format!("Version: {}", env!("CARGO_PKG_VERSION")));
+    out.detail(&format!("Git commit: {}", env!("GIT_COMMIT")));
+    out.detail(&format!("Build time (UTC): {}", env!("BUILD_TIME")));
+
+    if let Some(url) = &args.qr {
+        print_qrcode(url);
+    }
+
+    Ok(())
+}
+
+/// Compile and write once, returning the AST on success or a one-line error.
+pub fn rebuild(input_path: &Path, out_dir: &str, options: &BuildOptions) -> Result<Vec<Entry>, String> {
+    let ast = transcompiler::compile(input_path)
+        .map_err(|e| format!("{}: {}", input_path.display(), e))?;
+    convert::build_pages(&ast, out_dir, options)
+        .map_err(|e| format!("failed to write output to {}: {}", out_dir, e))?;
+    Ok(ast)
+}
+
+/// Print the concise one-line result of a rebuild; `tag` names the command in the
+/// `[tag]` prefix (`watch` for `--watch`, `serve` for the preview server).
+pub fn report_rebuild(result: &Result<Vec<Entry>, String>, started: Instant, out_dir: &str, tag: &str, out: &Reporter) {
+    let ms = started.elapsed().as_millis();
+    let ast = match result {
+        Ok(ast) => ast,
+        Err(e) => {
+            if out.is_json() {
+                out.emit(json!({ "event": "rebuild", "status": "error", "message": e }));
+            } else {
+                out.error(&format!("[{}] error: {}", tag, e));
+            }
+            return;
+        }
+    };
+
+    let findings = lint::lint(ast, &LintConfig::default());
+    let errors = findings.iter().filter(|f| f.level == Level::Error).count();
+    let warnings = findings.len() - errors;
+    let pages = ast.iter().filter(|e| matches!(e, Entry::Page { .. })).count();
+
+    if out.is_json() {
+        out.emit(json!({
+            "event": "rebuild",
+            "status": "ok",
+            "output": out_dir,
+            "pages": pages,
+            "errors": errors,
+            "warnings": warnings,
+            "duration_ms": ms,
+        }));
+    } else if findings.is_empty() {
+        out.info(&format!("[{}] built {} ({} pages) in {} ms", tag, out_dir, pages, ms));
+    } else {
+        // lint problems are worth seeing even with --quiet
+        let line = format!(
+            "[{}] built {} ({} pages) in {} ms with {} error(s), {} warning(s)",
+            tag, out_dir, pages, ms, errors, warnings
+        );
+        if errors > 0 {
+            out.error(&line);
+        } else {
+            out.info(&line);
+        }
+    }
+    if out.verbose {
+        for f in &findings {
+            out.detail(&f.to_human());
+        }
+    }
+}
+
+/// Rebuild on every change to the input or its imports until interrupted.
+fn watch(input_path: &Path, args: &BuildArgs, out: &Reporter) -> io::Result<()> {
+    let out_dir = &args.output;
+
+    let started = Instant::now();
+    report_rebuild(&rebuild(input_path, out_dir, &args.options()), started, out_dir, "watch", out);
+    let files = transcompiler::source_files(input_path);
+    out.info(&format!("[watch] watching {} file(s); press Ctrl+C to stop", files.len()));
+
+    watch_sources(
+        input_path,
+        || {
+            let started = Instant::now();
+            report_rebuild(&rebuild(input_path, out_dir, &args.options()), started, out_dir, "watch", out);
+        },
+        |files| out.detail(&format!("[watch] now watching {} file(s)", files.len())),
+    )
+}
@@ -0,0 +1,75 @@
+// === src/commands/check.rs ===
+
+use std::io;
+use std::process;
+
+use serde_json::json;
+
+use crate::commands::require_input_file;
+use crate::lint::{self, Level, LintConfig};
+use crate::messages::Reporter;
+use crate::transcompiler;
+
+#[derive(clap::Args, Debug)]
+pub struct CheckArgs {
+    /// Input .sqe file to check
+    #[arg(value_name = "FILE")]
+    pub input: String,
+
+    /// Treat lint warnings as errors
+    #[arg(long)]
+    pub strict: bool,
+}
+
+/// Compile and lint without writing any output. Exits non-zero when the file does not
+/// compile or when lint reports errors (or warnings with `--strict`).
+pub fn run(args: &CheckArgs, out: &Reporter) -> io::Result<()> {
+    let input_path = require_input_file(&args.input, out);
+
+    let ast = match transcompiler::compile(input_path) {
+        Ok(a) => a,
+        Err(e) => {
+            if out.is_json() {
+                out.emit(json!({
+                    "event": "check",
+                    "status": "error",
+                    "input": args.input,
+                    "message": e.to_string(),
+                }));
+            } else {
+                out.error(&format!("{}: error: {}", input_path.display(), e));
+            }
+            process::exit(1);
+        }
+    };
+
+    let findings = lint::lint(&ast, &LintConfig::default());
+    let failing = findings
+        .iter()
+        .filter(|f| f.level == Level::Error || (args.strict && f.level == Level::Warning))
+        .count();
+
+    if !(findings.is_empty() || out.quiet && failing == 0) {
+        if out.is_json() {
+            out.emit(lint::to_json(&findings));
+        } else {
+            println!("{}", lint::render(&findings, false));
+        }
+    }
+    if out.is_json() {
+        if !out.quiet || failing > 0 {
+            out.emit(json!({
+                "event": "check",
+                "status": if failing > 0 { "error" } else { "ok" },
+                "input": args.input,
+            }));
+        }
+    } else if failing == 0 {
+        out.info(&format!("{}: ok", input_path.display()));
+    }
+
+    if failing > 0 {
+        process::exit(1);
+    }
+    Ok(())
+}
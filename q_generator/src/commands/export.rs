@@ -0,0 +1,53 @@
+// === src/commands/export.rs ===
+
+use std::fs;
+use std::io;
+
+use crate::codebook::{self, CodebookFormat};
+use crate::commands::{compile_or_exit, require_input_file};
+use crate::export::questionnaire_json;
+use crate::messages::Reporter;
+
+#[derive(clap::Args, Debug)]
+pub struct ExportArgs {
+    /// Input .sqe file to export
+    #[arg(value_name = "FILE")]
+    pub input: String,
+
+    /// Write to this file instead of stdout
+    #[arg(long, short, value_name = "FILE")]
+    pub output: Option<String>,
+
+    /// Export a codebook (data dictionary) instead of the questionnaire structure
+    #[arg(long)]
+    pub codebook: bool,
+
+    /// Codebook format [default: from the output file extension, else markdown]
+    #[arg(long, value_enum, requires = "codebook")]
+    pub format: Option<CodebookFormat>,
+}
+
+/// Export the compiled questionnaire structure (pages, items, answer keys) as JSON,
+/// or with `--codebook` a data dictionary of its answer keys.
+pub fn run(args: &ExportArgs, out: &Reporter) -> io::Result<()> {
+    let input_path = require_input_file(&args.input, out);
+    let ast = compile_or_exit(input_path, out);
+    let doc = if args.codebook {
+        let format = args
+            .format
+            .or_else(|| args.output.as_deref().and_then(CodebookFormat::from_path))
+            .unwrap_or_default();
+        codebook::render(&ast, format).trim_end().to_string()
+    } else {
+        serde_json::to_string_pretty(&questionnaire_json(&ast))?
+    };
+
+    match &args.output {
+        Some(path) => {
+            fs::write(path, doc + "\n")?;
+            out.info(&format!("Wrote {}", path));
+        }
+        None => println!("{}", doc),
+    }
+    Ok(())
+}
@@ -0,0 +1,69 @@
+// === src/commands/fmt.rs ===
+
+use std::fs;
+use std::io;
+use std::process;
+
+use serde_json::json;
+
+use crate::commands::require_input_file;
+use crate::formatter::format_source;
+use crate::messages::Reporter;
+
+#[derive(clap::Args, Debug)]
+pub struct FmtArgs {
+    /// .sqe files to format in place
+    #[arg(value_name = "FILE", required = true)]
+    pub inputs: Vec<String>,
+
+    /// Do not write; exit with status 1 if any file would be reformatted
+    #[arg(long)]
+    pub check: bool,
+
+    /// Print the formatted source to stdout instead of writing the file
+    #[arg(long, conflicts_with = "check")]
+    pub stdout: bool,
+}
+
+pub fn run(args: &FmtArgs, out: &Reporter) -> io::Result<()> {
+    let mut unformatted = 0usize;
+
+    for input in &args.inputs {
+        let path = require_input_file(input, out);
+        let src = fs::read_to_string(path)?;
+        let formatted = match format_source(&src) {
+            Ok(f) => f,
+            Err(e) => {
+                out.error(&format!("Failed to format {}: {}", path.display(), e));
+                process::exit(1);
+            }
+        };
+
+        if args.stdout {
+            print!("{}", formatted);
+            continue;
+        }
+
+        let changed = formatted != src;
+        if args.check {
+            if changed {
+                unformatted += 1;
+                if out.is_json() {
+                    out.emit(json!({ "event": "fmt", "file": input, "status": "unformatted" }));
+                } else {
+                    out.error(&format!("Would reformat {}", path.display()));
+                }
+            }
+        } else if changed {
+            fs::write(path, &formatted)?;
+            out.info(&format!("Formatted {}", path.display()));
+        } else {
+            out.detail(&format!("Already formatted {}", path.display()));
+        }
+    }
+
+    if unformatted > 0 {
+        process::exit(1);
+    }
+    Ok(())
+}
@@ -0,0 +1,99 @@
+// === src/commands/lint.rs ===
+
+use std::io;
+use std::path::Path;
+use std::process;
+
+use crate::commands::{compile_or_exit, require_input_file};
+use crate::lint::{self, Level, LintConfig};
+use crate::messages::Reporter;
+
+#[derive(clap::Args, Debug)]
+pub struct LintArgs {
+    /// Input .sqe file to lint
+    #[arg(value_name = "FILE")]
+    pub input: String,
+
+    /// Output format for findings
+    #[arg(long, value_enum, default_value_t = LintFormat::Human)]
+    pub format: LintFormat,
+
+    /// JSON config file with rule levels and limits
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<String>,
+
+    /// Disable a rule (repeatable)
+    #[arg(long, value_name = "RULE")]
+    pub allow: Vec<String>,
+
+    /// Report a rule as a warning (repeatable)
+    #[arg(long, value_name = "RULE")]
+    pub warn: Vec<String>,
+
+    /// Report a rule as an error (repeatable)
+    #[arg(long, value_name = "RULE")]
+    pub deny: Vec<String>,
+
+    /// Maximum question length in characters for the long-question rule
+    #[arg(long, value_name = "N")]
+    pub max_question_length: Option<usize>,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LintFormat {
+    Human,
+    Json,
+}
+
+impl LintArgs {
+    /// Build the effective lint configuration: defaults, then the config file, then flags.
+    pub fn config(&self, out: &Reporter) -> LintConfig {
+        let mut config = LintConfig::default();
+        if let Some(path) = &self.config
+            && let Err(e) = config.load_file(Path::new(path))
+        {
+            out.error(&format!("Failed to load lint config {}: {}", path, e));
+            process::exit(2);
+        }
+        let overrides = [
+            (&self.allow, Level::Allow),
+            (&self.warn, Level::Warning),
+            (&self.deny, Level::Error),
+        ];
+        for (rules, level) in overrides {
+            for rule in rules {
+                if let Err(e) = config.set_level(rule, level) {
+                    out.error(&e);
+                    process::exit(2);
+                }
+            }
+        }
+        if let Some(max) = self.max_question_length {
+            config.max_question_length = max;
+        }
+        config
+    }
+}
+
+pub fn run(args: &LintArgs, out: &Reporter) -> io::Result<()> {
+    let input_path = require_input_file(&args.input, out);
+    let config = args.config(out);
+    let ast = compile_or_exit(input_path, out);
+
+    let findings = lint::lint(&ast, &config);
+    let has_errors = findings.iter().any(|f| f.level == Level::Error);
+    // Findings are the command's result, so they are printed even with --quiet
+    // unless there is nothing to report.
+    if !(out.quiet && findings.is_empty()) {
+        // with --message-format json the document is one record like every other message
+        if out.is_json() {
+            out.emit(lint::to_json(&findings));
+        } else {
+            println!("{}", lint::render(&findings, args.format == LintFormat::Json));
+        }
+    }
+    if has_errors {
+        process::exit(1);
+    }
+    Ok(())
+}
@@ -0,0 +1,137 @@
+// === src/commands/merge.rs ===
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+
+use serde_json::Value;
+
+use crate::commands::{compile_or_exit, load_responses_or_exit, require_input_file};
+use crate::export::{answer_columns, answer_labels, content_hash, multi_separator};
+use crate::messages::Reporter;
+use crate::responses::split_response;
+use crate::table::{TableFormat, answer_cell, row};
+
+/// Metadata columns written before the answer columns.
+const META_COLUMNS: [&str; 5] = ["source", "id", "hash", "started_at", "finished_at"];
+
+#[derive(clap::Args, Debug)]
+pub struct MergeArgs {
+    /// Questionnaire the answers were collected with (defines the column order)
+    #[arg(value_name = "FILE")]
+    pub questionnaire: String,
+
+    /// answers.json files, responses.jsonl stores, or directories containing them
+    #[arg(value_name = "ANSWERS", required = true)]
+    pub inputs: Vec<String>,
+
+    /// Write to this file instead of stdout
+    #[arg(long, short, value_name = "FILE")]
+    pub output: Option<String>,
+
+    /// Output format [default: tsv for a .tsv output file, else csv]
+    #[arg(long, value_enum)]
+    pub format: Option<TableFormat>,
+
+    /// Separator for multi-select answers [default: the questionnaire's multi-separator, else ";"]
+    #[arg(long, value_name = "SEP")]
+    pub multi_separator: Option<String>,
+
+    /// Write option labels instead of option values
+    #[arg(long)]
+    pub labels: bool,
+}
+
+/// Merge saved responses into one wide table with one row per response and the
+/// answer columns in questionnaire order. Keys the questionnaire does not define are
+/// appended after its own columns.
+pub fn run(args: &MergeArgs, out: &Reporter) -> io::Result<()> {
+    let input_path = require_input_file(&args.questionnaire, out);
+    let ast = compile_or_exit(input_path, out);
+    let hash = content_hash(&ast);
+    let labels = answer_labels(&ast);
+    let sep = args.multi_separator.clone().unwrap_or_else(|| multi_separator(&ast));
+    let format = args.format.unwrap_or(match &args.output {
+        Some(p) if p.to_ascii_lowercase().ends_with(".tsv") => TableFormat::Tsv,
+        _ => TableFormat::Csv,
+    });
+
+    let responses = load_responses_or_exit(&args.inputs, out);
+
+    let mut rows = Vec::new();
+    for (source, record) in &responses {
+        match split_response(record) {
+            Some((meta, answers)) => {
+                if let Some(h) = meta.get("hash").and_then(Value::as_str)
+                    && h != hash
+                {
+                    out.error(&format!(
+                        "warning: {} was saved from a different version of {}",
+                        source, args.questionnaire
+                    ));
+                }
+                rows.push((source, meta, answers));
+            }
+            None => out.error(&format!("warning: skipping {}: not a JSON object", source)),
+        }
+    }
+
+    let mut columns = answer_columns(&ast);
+    let known: BTreeSet<&String> = columns.iter().collect();
+    let extra: BTreeSet<String> = rows
+        .iter()
+        .flat_map(|(_, _, answers)| answers.keys())
+        .filter(|k| !known.contains(k))
+        .cloned()
+        .collect();
+    if !extra.is_empty() {
+        out.detail(&format!(
+            "Keys not defined by the questionnaire: {}",
+            extra.iter().cloned().collect::<Vec<_>>().join(", ")
+        ));
+    }
+    columns.extend(extra);
+
+    let delimiter = format.delimiter();
+    let mut lines = Vec::with_capacity(rows.len() + 1);
+    let header: Vec<&str> = META_COLUMNS.iter().copied().chain(columns.iter().map(String::as_str)).collect();
+    lines.push(row(&header, delimiter));
+    for (source, meta, answers) in &rows {
+        let mut fields = vec![source.to_string()];
+        fields.extend(META_COLUMNS[1..].iter().map(|m| answer_cell(meta.get(*m), &sep)));
+        for key in &columns {
+            let value = answers.get(key);
+            let cell = match (args.labels, value) {
+                (true, Some(v)) => answer_cell(Some(&label_of(&labels[key], v)), &sep),
+                _ => answer_cell(value, &sep),
+            };
+            fields.push(cell);
+        }
+        lines.push(row(&fields, delimiter));
+    }
+    let text = lines.join("\n") + "\n";
+
+    match &args.output {
+        Some(path) => {
+            fs::write(path, text)?;
+            out.info(&format!("Wrote {} response(s) to {}", rows.len(), path));
+        }
+        None => print!("{}", text),
+    }
+    Ok(())
+}
+
+/// Replace option values by their labels; values without a label are kept.
+fn label_of(options: &Value, value: &Value) -> Value {
+    match value {
+        Value::Array(items) => Value::Array(items.iter().map(|v| label_of(options, v)).collect()),
+        Value::Null => Value::Null,
+        v => {
+            let key = match v {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            options.get(&key).cloned().unwrap_or_else(|| v.clone())
+        }
+    }
+}
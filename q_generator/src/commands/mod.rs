@@ -0,0 +1,68 @@
+// === src/commands/mod.rs ===
+
+//! One module per CLI subcommand. Each module defines its clap `Args` struct and a
+//! `run` function; `main.rs` only parses the command line and dispatches here.
+
+pub mod build;
+pub mod check;
+pub mod export;
+pub mod fmt;
+pub mod lint;
+pub mod merge;
+pub mod new;
+pub mod report;
+pub mod score;
+pub mod serve;
+pub mod stats;
+pub mod validate_answers;
+
+use std::path::Path;
+use std::process;
+
+use serde_json::Value;
+
+use crate::messages::Reporter;
+use crate::responses::load_responses;
+use crate::transcompiler::{self, Entry};
+
+/// Validate that the input path exists and is a file, exiting with status 2 otherwise.
+pub fn require_input_file<'a>(input: &'a str, out: &Reporter) -> &'a Path {
+    let input_path = Path::new(input);
+    if !input_path.exists() {
+        out.error(&format!("Input file does not exist: {}", input_path.display()));
+        out.error("Run with --help for usage information.");
+        process::exit(2);
+    }
+    if !input_path.is_file() {
+        out.error(&format!("Input path is not a file: {}", input_path.display()));
+        process::exit(2);
+    }
+    input_path
+}
+
+/// Compile a questionnaire, exiting with status 1 on failure.
+pub fn compile_or_exit(input_path: &Path, out: &Reporter) -> Vec<Entry> {
+    match transcompiler::compile(input_path) {
+        Ok(a) => a,
+        Err(e) => {
+            out.error(&format!("Failed to compile {}: {}", input_path.display(), e));
+            process::exit(1);
+        }
+    }
+}
+
+/// Load the saved responses from every input (files, stores or directories), in order,
+/// exiting with status 1 when one cannot be read.
+pub fn load_responses_or_exit(inputs: &[String], out: &Reporter) -> Vec<(String, Value)> {
+    let mut responses = Vec::new();
+    for input in inputs {
+        match load_responses(Path::new(input)) {
+            Ok(r) => responses.extend(r),
+            Err(e) => {
+                out.error(&format!("Failed to read {}: {}", input, e));
+                process::exit(1);
+            }
+        }
+    }
+    responses
+}
@@ -0,0 +1,64 @@
+// === src/commands/new.rs ===
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process;
+
+use crate::messages::Reporter;
+
+/// Starter questionnaire written by `new`.
+const TEMPLATE: &str = r#"title "{title}"
+
+@p "Welcome"
+insert {
+    Thank you for taking part. Please answer every question honestly.
+}
+
+@p "Questions"
+choice q1 {
+Do you enjoy surveys?
+Yes >> 1
+No >> 0
+}
+"#;
+
+#[derive(clap::Args, Debug)]
+pub struct NewArgs {
+    /// Path of the .sqe file to create
+    #[arg(value_name = "FILE")]
+    pub path: String,
+
+    /// Questionnaire title (defaults to the file name)
+    #[arg(long)]
+    pub title: Option<String>,
+
+    /// Overwrite the file if it already exists
+    #[arg(long)]
+    pub force: bool,
+}
+
+pub fn run(args: &NewArgs, out: &Reporter) -> io::Result<()> {
+    let path = Path::new(&args.path);
+    if path.exists() && !args.force {
+        out.error(&format!("{} already exists (use --force to overwrite)", path.display()));
+        process::exit(2);
+    }
+
+    let title = args.title.clone().unwrap_or_else(|| {
+        path.file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "Survey".to_string())
+    });
+    // `title` values are read up to the next quote, so drop any quotes in the name.
+    let src = TEMPLATE.replace("{title}", &title.replace('"', ""));
+
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, src)?;
+    out.info(&format!("Created {}", path.display()));
+    Ok(())
+}
@@ -0,0 +1,44 @@
+// === src/commands/report.rs ===
+
+use std::fs;
+use std::io;
+
+use crate::commands::{compile_or_exit, load_responses_or_exit, require_input_file};
+use crate::messages::Reporter;
+use crate::report;
+use crate::responses::split_response;
+
+#[derive(clap::Args, Debug)]
+pub struct ReportArgs {
+    /// Questionnaire the answers were collected with
+    #[arg(value_name = "FILE")]
+    pub questionnaire: String,
+
+    /// answers.json files, responses.jsonl stores, or directories containing them
+    #[arg(value_name = "ANSWERS", required = true)]
+    pub inputs: Vec<String>,
+
+    /// Report file to write
+    #[arg(long, short, value_name = "FILE", default_value = "report.html")]
+    pub output: String,
+}
+
+/// Write a static HTML report (frequencies, completion, scale distributions) for a set
+/// of responses.
+pub fn run(args: &ReportArgs, out: &Reporter) -> io::Result<()> {
+    let input_path = require_input_file(&args.questionnaire, out);
+    let ast = compile_or_exit(input_path, out);
+
+    let records = load_responses_or_exit(&args.inputs, out);
+    let mut answers = Vec::new();
+    for (source, record) in &records {
+        match split_response(record) {
+            Some((_, a)) => answers.push(a),
+            None => out.error(&format!("warning: skipping {}: not a JSON object", source)),
+        }
+    }
+
+    fs::write(&args.output, report::render(&ast, &answers))?;
+    out.info(&format!("Wrote report over {} response(s) to {}", answers.len(), args.output));
+    Ok(())
+}
@@ -0,0 +1,140 @@
+// === src/commands/score.rs ===
+
+use std::fs;
+use std::io;
+use std::process;
+
+use serde_json::{Map, Value, json};
+
+use crate::commands::{compile_or_exit, load_responses_or_exit, require_input_file};
+use crate::grading::{self, QuizGrade};
+use crate::messages::Reporter;
+use crate::responses::split_response;
+use crate::scoring::{Scorer, number};
+use crate::table::row;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScoreFormat {
+    Json,
+    Csv,
+    Tsv,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ScoreArgs {
+    /// Questionnaire that declares the scales or `mode quiz`
+    #[arg(value_name = "FILE")]
+    pub questionnaire: String,
+
+    /// answers.json files, responses.jsonl stores, or directories containing them
+    #[arg(value_name = "ANSWERS", required = true)]
+    pub inputs: Vec<String>,
+
+    /// Write to this file instead of stdout
+    #[arg(long, short, value_name = "FILE")]
+    pub output: Option<String>,
+
+    /// Output format [default: from the output file extension, else json]
+    #[arg(long, value_enum)]
+    pub format: Option<ScoreFormat>,
+}
+
+impl ScoreArgs {
+    fn format(&self) -> ScoreFormat {
+        self.format.unwrap_or_else(|| {
+            let out = self.output.as_deref().unwrap_or("").to_ascii_lowercase();
+            if out.ends_with(".csv") {
+                ScoreFormat::Csv
+            } else if out.ends_with(".tsv") {
+                ScoreFormat::Tsv
+            } else {
+                ScoreFormat::Json
+            }
+        })
+    }
+}
+
+/// Compute the declared scale scores, and the quiz grade in `mode quiz`, for every
+/// response.
+pub fn run(args: &ScoreArgs, out: &Reporter) -> io::Result<()> {
+    let input_path = require_input_file(&args.questionnaire, out);
+    let ast = compile_or_exit(input_path, out);
+    let scorer = Scorer::new(&ast);
+    let quiz = grading::quiz(&ast).is_some();
+    if scorer.scales.is_empty() && !quiz {
+        out.error(&format!("{} declares no scales and no `mode quiz`", args.questionnaire));
+        process::exit(1);
+    }
+    let graded = grading::graded_items(&ast);
+
+    let mut results = Vec::new();
+    for (source, record) in load_responses_or_exit(&args.inputs, out) {
+        match split_response(&record) {
+            Some((meta, answers)) => {
+                let id = meta.get("id").cloned().unwrap_or(Value::Null);
+                let grade = quiz.then(|| grading::grade(&ast, answers));
+                results.push((source, id, scorer.score(answers), grade));
+            }
+            None => out.error(&format!("warning: skipping {}: not a JSON object", source)),
+        }
+    }
+
+    let text = match args.format() {
+        ScoreFormat::Json => {
+            let list: Vec<Value> = results
+                .iter()
+                .map(|(source, id, scores, grade)| {
+                    let by_scale: Map<String, Value> = scores.iter().map(|s| (s.name.clone(), s.to_json())).collect();
+                    let mut result = json!({ "source": source, "id": id, "scores": by_scale });
+                    if let Some(grade) = grade {
+                        result["quiz"] = grade.to_json();
+                    }
+                    result
+                })
+                .collect();
+            serde_json::to_string_pretty(&list)? + "\n"
+        }
+        format => {
+            let delimiter = if format == ScoreFormat::Tsv { '\t' } else { ',' };
+            let mut header = vec!["source".to_string(), "id".to_string()];
+            for scale in &scorer.scales {
+                header.push(scale.name.clone());
+                if scale.norms.is_some() {
+                    header.extend(["percentile", "t", "band"].map(|c| format!("{}_{}", scale.name, c)));
+                }
+            }
+            if quiz {
+                header.extend(["quiz_points".to_string(), "quiz_max".to_string()]);
+                header.extend(graded.iter().map(|(key, _)| format!("{}_points", key)));
+            }
+            let mut lines = vec![row(&header, delimiter)];
+            for (source, id, scores, grade) in &results {
+                let mut fields = vec![source.clone(), id.as_str().unwrap_or("").to_string()];
+                let cell = |x: Option<f64>| x.map(|x| number(x).to_string()).unwrap_or_default();
+                for (scale, s) in scorer.scales.iter().zip(scores) {
+                    fields.push(cell(s.score));
+                    if scale.norms.is_some() {
+                        let norm = s.norm.clone().unwrap_or_default();
+                        fields.extend([cell(norm.percentile), cell(norm.t), norm.band.unwrap_or_default()]);
+                    }
+                }
+                if let Some(QuizGrade { points, max, items }) = grade {
+                    fields.extend([cell(Some(*points)), cell(Some(*max))]);
+                    // questions a draw did not pick are left empty
+                    fields.extend(graded.iter().map(|(key, _)| cell(items.iter().find(|i| i.key == *key).map(|i| i.points))));
+                }
+                lines.push(row(&fields, delimiter));
+            }
+            lines.join("\n") + "\n"
+        }
+    };
+
+    match &args.output {
+        Some(path) => {
+            fs::write(path, text)?;
+            out.info(&format!("Scored {} response(s) into {}", results.len(), path));
+        }
+        None => print!("{}", text),
+    }
+    Ok(())
+}
@@ -0,0 +1,242 @@
+// === src/commands/serve.rs ===
+
+use std::io::{self, Write};
+use std::net::{IpAddr, TcpListener, TcpStream, UdpSocket};
+use std::path::PathBuf;
+use std::process;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde_json::{Value, json};
+
+use crate::commands::build::{rebuild, report_rebuild};
+use crate::commands::require_input_file;
+use crate::convert::BuildOptions;
+use crate::items::qrcode::print_qrcode;
+use crate::items::submit::DEFAULT_ENDPOINT;
+use crate::messages::Reporter;
+use crate::responses::{DEFAULT_STORE, ResponseStore};
+use crate::server::{self, Request, Response};
+use crate::watch::watch_sources;
+
+/// Server-sent events endpoint the preview pages listen on for reloads.
+const EVENTS_PATH: &str = "/__sqe/events";
+
+/// Injected into served HTML pages only (never into `build` output). Remembers the
+/// current page in sessionStorage so a reload lands on the same page.
+const RELOAD_CLIENT_JS: &str = r#"<script>
+document.addEventListener("DOMContentLoaded", () => {
+    const KEY = "sqe:preview:page";
+    const SQE = window.SQE || {};
+    const saved = parseInt(sessionStorage.getItem(KEY) || "", 10);
+    if (Number.isFinite(saved) && typeof SQE.showPage === "function") SQE.showPage(saved);
+    sessionStorage.removeItem(KEY);
+    if (!window.EventSource) return;
+    const es = new EventSource("/__sqe/events");
+    es.addEventListener("reload", () => {
+        try { if (typeof SQE.currentPage === "function") sessionStorage.setItem(KEY, String(SQE.currentPage())); } catch(e) {}
+        location.reload();
+    });
+});
+</script>"#;
+
+#[derive(clap::Args, Debug)]
+pub struct ServeArgs {
+    /// Input .sqe file to compile and serve
+    #[arg(value_name = "FILE")]
+    pub input: String,
+
+    /// Output directory for the compiled pages
+    #[arg(long, short, value_name = "DIR", default_value = "out")]
+    pub output: String,
+
+    /// Address to bind; use 0.0.0.0 to preview from other devices on the LAN
+    #[arg(long, default_value = "127.0.0.1")]
+    pub host: IpAddr,
+
+    /// Port to listen on
+    #[arg(long, short, default_value_t = 8000)]
+    pub port: u16,
+
+    /// Do not rebuild and reload when the source changes
+    #[arg(long)]
+    pub no_reload: bool,
+
+    /// JSONL file that responses POSTed to the collection endpoint are appended to
+    #[arg(long, value_name = "FILE", default_value = DEFAULT_STORE)]
+    pub responses: String,
+
+    /// Leave quiz answers, points and feedback out of the page (grade with `score`)
+    #[arg(long)]
+    pub hide_answers: bool,
+}
+
+impl ServeArgs {
+    fn options(&self) -> BuildOptions {
+        BuildOptions { hide_answers: self.hide_answers }
+    }
+}
+
+/// Best guess at this machine's LAN address, used for the URL shown to phones.
+/// Connecting a UDP socket sends no packets; it only asks the OS which interface it
+/// would route through.
+fn lan_address() -> Option<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("192.168.0.1:9").ok()?;
+    let ip = socket.local_addr().ok()?.ip();
+    if ip.is_unspecified() || ip.is_loopback() { None } else { Some(ip) }
+}
+
+/// CORS headers so pages opened from elsewhere (file://, another port) can `submit` here.
+fn with_cors(mut resp: Response) -> Response {
+    resp.headers.push(("Access-Control-Allow-Origin".into(), "*".into()));
+    resp.headers.push(("Access-Control-Allow-Methods".into(), "POST, PUT, OPTIONS".into()));
+    resp.headers.push(("Access-Control-Allow-Headers".into(), "Content-Type, Authorization".into()));
+    resp
+}
+
+/// Handle a POST to the collection endpoint: validate the JSON body and store it.
+fn collect_response(req: &Request, store: &ResponseStore, out: &Reporter) -> Response {
+    if req.method == "OPTIONS" {
+        return with_cors(Response::new(204, "text/plain", Vec::new()));
+    }
+    if req.method != "POST" && req.method != "PUT" {
+        return Response::text(405, "method not allowed");
+    }
+    let payload: Value = match serde_json::from_slice(&req.body) {
+        Ok(v @ Value::Object(_)) => v,
+        _ => return Response::text(400, "expected a JSON object"),
+    };
+    match store.append(payload) {
+        Ok(record) => {
+            out.info(&format!(
+                "[responses] stored {} in {}",
+                record["id"].as_str().unwrap_or(""),
+                store.path().display()
+            ));
+            Response::json(201, &json!({ "id": record["id"], "received_at": record["received_at"] }))
+        }
+        Err(e) => {
+            out.error(&format!("[responses] failed to store response: {}", e));
+            Response::text(500, "failed to store response")
+        }
+    }
+}
+
+/// Insert the reload client before the closing body tag of an HTML page.
+fn inject_reload_client(body: Vec<u8>) -> Vec<u8> {
+    let html = String::from_utf8_lossy(&body);
+    match html.rfind("</body>") {
+        Some(pos) => format!("{}{}\n{}", &html[..pos], RELOAD_CLIENT_JS, &html[pos..]).into_bytes(),
+        None => format!("{}{}", html, RELOAD_CLIENT_JS).into_bytes(),
+    }
+}
+
+/// Keep an event stream open and send `reload` whenever `generation` moves on.
+fn stream_events(stream: &mut TcpStream, generation: &AtomicU64) -> io::Result<()> {
+    stream.write_all(
+        b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-store\r\nConnection: keep-alive\r\n\r\n",
+    )?;
+    stream.write_all(b": connected\n\n")?;
+    stream.flush()?;
+
+    let mut seen = generation.load(Ordering::SeqCst);
+    let mut last_ping = Instant::now();
+    loop {
+        thread::sleep(Duration::from_millis(200));
+        let current = generation.load(Ordering::SeqCst);
+        if current != seen {
+            seen = current;
+            stream.write_all(format!("event: reload\ndata: {}\n\n", current).as_bytes())?;
+            stream.flush()?;
+        } else if last_ping.elapsed() > Duration::from_secs(15) {
+            // comment lines keep proxies and idle browsers from dropping the stream
+            stream.write_all(b": ping\n\n")?;
+            stream.flush()?;
+            last_ping = Instant::now();
+        }
+    }
+}
+
+/// Build the questionnaire, serve the output directory, and rebuild + reload open
+/// browser tabs whenever the source or its imports change. Responses POSTed to
+/// the collection endpoint are appended to the `--responses` store.
+pub fn run(args: &ServeArgs, out: &Reporter) -> io::Result<()> {
+    let input_path = require_input_file(&args.input, out).to_path_buf();
+    let out_dir = args.output.clone();
+
+    let started = Instant::now();
+    let options = args.options();
+    let first = rebuild(&input_path, &out_dir, &options);
+    report_rebuild(&first, started, &out_dir, "serve", out);
+    if first.is_err() {
+        process::exit(1);
+    }
+
+    let listener = match TcpListener::bind((args.host, args.port)) {
+        Ok(l) => l,
+        Err(e) => {
+            out.error(&format!("Failed to listen on {}:{}: {}", args.host, args.port, e));
+            process::exit(1);
+        }
+    };
+    let port = listener.local_addr()?.port();
+
+    out.info(&format!("Serving {} at http://localhost:{}/", out_dir, port));
+    if !args.host.is_loopback() {
+        let lan_ip = if args.host.is_unspecified() { lan_address() } else { Some(args.host) };
+        if let Some(ip) = lan_ip {
+            let lan_url = format!("http://{}:{}/", ip, port);
+            out.info(&format!("On your network: {}", lan_url));
+            if !out.quiet && !out.is_json() {
+                print_qrcode(&lan_url);
+            }
+        }
+    }
+    out.info(&format!("Collecting responses at {} into {}", DEFAULT_ENDPOINT, args.responses));
+    out.info("Press Ctrl+C to stop.");
+
+    let generation = Arc::new(AtomicU64::new(0));
+    let reload = !args.no_reload;
+
+    if reload {
+        let generation = Arc::clone(&generation);
+        let reporter = *out;
+        thread::spawn(move || {
+            watch_sources(
+                &input_path,
+                || {
+                    let started = Instant::now();
+                    let result = rebuild(&input_path, &out_dir, &options);
+                    report_rebuild(&result, started, &out_dir, "serve", &reporter);
+                    // Keep showing the last good build when the new one fails.
+                    if result.is_ok() {
+                        generation.fetch_add(1, Ordering::SeqCst);
+                    }
+                },
+                |files| reporter.detail(&format!("[serve] now watching {} file(s)", files.len())),
+            )
+        });
+    }
+
+    let root = PathBuf::from(&args.output);
+    let store = Arc::new(ResponseStore::new(&args.responses));
+    let reporter = *out;
+    server::serve(listener, move |req: &Request, stream: &mut TcpStream| {
+        if req.path == DEFAULT_ENDPOINT {
+            return Ok(Some(with_cors(collect_response(req, &store, &reporter))));
+        }
+        if reload && req.path == EVENTS_PATH {
+            // the client simply reconnects if this fails
+            let _ = stream_events(stream, &generation);
+            return Ok(None);
+        }
+        let mut resp: Response = server::static_file(&root, req);
+        if reload && resp.status == 200 && resp.content_type.starts_with("text/html") {
+            resp.body = inject_reload_client(resp.body);
+        }
+        Ok(Some(resp))
+    })
+}
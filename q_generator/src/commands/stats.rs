@@ -0,0 +1,70 @@
+// === src/commands/stats.rs ===
+
+use std::fs;
+use std::io;
+use std::process;
+
+use serde_json::Value;
+
+use crate::commands::{compile_or_exit, load_responses_or_exit, require_input_file};
+use crate::messages::Reporter;
+use crate::psychometrics::all_scale_stats;
+use crate::responses::split_response;
+use crate::scoring::Scorer;
+
+#[derive(clap::Args, Debug)]
+pub struct StatsArgs {
+    /// Questionnaire that declares the scales
+    #[arg(value_name = "FILE")]
+    pub questionnaire: String,
+
+    /// answers.json files, responses.jsonl stores, or directories containing them
+    #[arg(value_name = "ANSWERS", required = true)]
+    pub inputs: Vec<String>,
+
+    /// Write JSON instead of a text table
+    #[arg(long)]
+    pub json: bool,
+
+    /// Write to this file instead of stdout
+    #[arg(long, short, value_name = "FILE")]
+    pub output: Option<String>,
+}
+
+/// Reliability (alpha, omega) and item statistics for every declared scale.
+pub fn run(args: &StatsArgs, out: &Reporter) -> io::Result<()> {
+    let input_path = require_input_file(&args.questionnaire, out);
+    let ast = compile_or_exit(input_path, out);
+    let scorer = Scorer::new(&ast);
+    if scorer.scales.is_empty() {
+        out.error(&format!("{} declares no scales", args.questionnaire));
+        process::exit(1);
+    }
+
+    let records = load_responses_or_exit(&args.inputs, out);
+    let mut answers = Vec::new();
+    for (source, record) in &records {
+        match split_response(record) {
+            Some((_, a)) => answers.push(a),
+            None => out.error(&format!("warning: skipping {}: not a JSON object", source)),
+        }
+    }
+
+    let stats = all_scale_stats(&scorer, &answers);
+    let json = args.json || args.output.as_deref().is_some_and(|p| p.to_ascii_lowercase().ends_with(".json"));
+    let text = if json {
+        let list: Vec<Value> = stats.iter().map(|s| s.to_json()).collect();
+        serde_json::to_string_pretty(&list)? + "\n"
+    } else {
+        stats.iter().map(|s| s.to_text()).collect::<Vec<_>>().join("\n")
+    };
+
+    match &args.output {
+        Some(path) => {
+            fs::write(path, text)?;
+            out.info(&format!("Wrote statistics over {} response(s) to {}", answers.len(), path));
+        }
+        None => print!("{}", text),
+    }
+    Ok(())
+}
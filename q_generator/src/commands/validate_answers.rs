@@ -0,0 +1,84 @@
+// === src/commands/validate_answers.rs ===
+
+use std::fs;
+use std::io;
+use std::process;
+
+use serde_json::{Value, json};
+
+use crate::commands::{compile_or_exit, load_responses_or_exit, require_input_file};
+use crate::messages::Reporter;
+use crate::schema::{answers_schema, validate};
+
+#[derive(clap::Args, Debug)]
+pub struct ValidateAnswersArgs {
+    /// Questionnaire (.sqe) or a generated answers.schema.json
+    #[arg(value_name = "FILE")]
+    pub schema: String,
+
+    /// answers.json files, responses.jsonl stores, or directories containing them
+    #[arg(value_name = "ANSWERS", required = true)]
+    pub inputs: Vec<String>,
+}
+
+/// The answers record inside a saved response: store lines are unwrapped and bare
+/// answer maps (older answers.json files) are treated as `{"answers": ...}`.
+fn as_record(value: &Value) -> Value {
+    if let Some(inner @ Value::Object(_)) = value.get("response") {
+        return as_record(inner);
+    }
+    match value.get("answers") {
+        Some(Value::Object(_)) => value.clone(),
+        _ if value.is_object() => json!({ "answers": value }),
+        _ => value.clone(),
+    }
+}
+
+/// Validate saved responses against the answers schema. Exits non-zero when any
+/// response has violations.
+pub fn run(args: &ValidateAnswersArgs, out: &Reporter) -> io::Result<()> {
+    let schema_path = require_input_file(&args.schema, out);
+    let schema = if schema_path.extension().and_then(|e| e.to_str()) == Some("json") {
+        match serde_json::from_str(&fs::read_to_string(schema_path)?) {
+            Ok(s) => s,
+            Err(e) => {
+                out.error(&format!("Invalid schema {}: {}", schema_path.display(), e));
+                process::exit(1);
+            }
+        }
+    } else {
+        answers_schema(&compile_or_exit(schema_path, out))
+    };
+
+    let mut checked = 0usize;
+    let mut invalid = 0usize;
+    for (source, value) in load_responses_or_exit(&args.inputs, out) {
+        checked += 1;
+        let errors = validate(&schema, &as_record(&value));
+        if !errors.is_empty() {
+            invalid += 1;
+        }
+        if out.is_json() {
+            out.emit(json!({
+                "event": "validate-answers",
+                "source": source,
+                "valid": errors.is_empty(),
+                "errors": errors,
+            }));
+        } else if errors.is_empty() {
+            out.detail(&format!("{}: ok", source));
+        } else {
+            for e in &errors {
+                println!("{}: {}", source, e);
+            }
+        }
+    }
+
+    if !out.is_json() {
+        out.info(&format!("{} of {} response(s) valid", checked - invalid, checked));
+    }
+    if invalid > 0 {
+        process::exit(1);
+    }
+    Ok(())
+}
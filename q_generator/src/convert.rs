@@ -1,49 +1,153 @@
 // === src/convert.rs ===
 
-use std::fs::{File, create_dir_all};
+use std::fs::{self, File, create_dir_all};
 use std::io::{self, Write};
 use std::path::Path;
 
+use crate::export::{
+    answer_columns, answer_labels, choice_items, computed, content_hash, document_lang, draws, input_keys,
+    multi_separator,
+};
+use crate::expr;
+use crate::grading;
+use crate::items::computed::evaluation_order;
+use crate::items::navigation::{NavigationSettings, Toc};
+use crate::items::Submit;
 use crate::items::common::{escape_html, to_js_string};
+use crate::schema::{SCHEMA_FILE, answers_schema};
+use crate::scoring::Scorer;
 use crate::transcompiler::{Entry, Question};
 
-pub fn build_pages(ast: &[Entry], out_dir: &str) -> io::Result<()> {
-    create_dir_all(out_dir)?;
+use serde_json::{Value, json};
 
-    // Collect document-level title (if any) and pages.
-    let mut pages: Vec<(String, &Vec<Question>)> = Vec::new();
-    let mut doc_title_opt: Option<String> = None;
-    for entry in ast {
-        match entry {
-            Entry::DocTitle(t) => {
-                // first DocTitle wins; later ones overwrite previous
-                doc_title_opt = Some(t.clone());
-            }
-            Entry::Page { title, content } => {
-                pages.push((title.clone(), content));
+/// Build settings that are not part of the questionnaire source.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BuildOptions {
+    /// Leave the quiz grading data (correct options, points, feedback) out of the page;
+    /// the saved answers are then graded with `sqe-core score`.
+    pub hide_answers: bool,
+}
+
+/// Seeded randomisation: the generator behind `SQE.random()` and the `draw` picks
+/// (`SQE.drawItems`, `SQE.undrawnKeys` over `SQE_DRAWS`). Kept apart from the page
+/// runtime so the tests can run it in node.
+const DRAW_JS: &str = r#"window.SQE = window.SQE || {};
+(function() {
+    const SQE = window.SQE;
+    // mulberry32: a generator of numbers in [0, 1) for a 32-bit seed
+    SQE.seededRandom = function(seed) {
+        let state = seed >>> 0;
+        return function() {
+            let t = (state = (state + 0x6D2B79F5) >>> 0);
+            t = Math.imul(t ^ (t >>> 15), t | 1);
+            t ^= t + Math.imul(t ^ (t >>> 7), t | 61);
+            return ((t ^ (t >>> 14)) >>> 0) / 4294967296;
+        };
+    };
+    // Pick the questions of every `draw` that has no pick in the answers yet: `count`
+    // of its questions, seeded by the session seed and the draw key (so scripts using
+    // SQE.random() do not change it). The pick is stored in bank order.
+    SQE.drawItems = function(answers) {
+        answers = answers || window.SQE_ANSWERS || {};
+        SQE_DRAWS.forEach(d => {
+            const keys = d.items.map(([key]) => key);
+            const picked = answers[d.key];
+            if (Array.isArray(picked) && picked.length === d.count && picked.every(k => keys.includes(k))) return;
+            let seed = SQE.seed;
+            for (let i = 0; i < d.key.length; i++) seed = Math.imul(seed ^ d.key.charCodeAt(i), 16777619) >>> 0;
+            const random = SQE.seededRandom(seed);
+            const order = keys.slice();
+            for (let i = 0; i < d.count; i++) {
+                const j = i + Math.floor(random() * (order.length - i));
+                [order[i], order[j]] = [order[j], order[i]];
             }
-            _ => {}
-        }
-    }
- 
-    let index_path = Path::new(out_dir).join("index.html");
-    let mut f = File::create(&index_path)?;
- 
-    // Determine document title: prefer explicit DocTitle, else first page title, else fallback.
-    let doc_title = if let Some(ref t) = doc_title_opt {
-        t.clone()
-    } else if !pages.is_empty() {
-        pages[0].0.clone()
-    } else {
-        "Survey".to_string()
+            const chosen = new Set(order.slice(0, d.count));
+            answers[d.key] = keys.filter(k => chosen.has(k));
+        });
+    };
+    // Questions of a draw the answers did not pick.
+    SQE.undrawnKeys = function(answers) {
+        answers = answers || window.SQE_ANSWERS || {};
+        const out = new Set();
+        SQE_DRAWS.forEach(d => {
+            const picked = Array.isArray(answers[d.key]) ? answers[d.key] : [];
+            d.items.forEach(([key]) => { if (!picked.includes(key)) out.add(key); });
+        });
+        return out;
     };
  
+})();"#;
+
+/// Paradata recorder behind `SQE.paradata()`: time on each page, time to first answer
+/// and answer changes per question, and back navigation. Times are passed in (ms) so
+/// the tests can run it in node.
+const PARADATA_JS: &str = r#"window.SQE = window.SQE || {};
+// The recorded data is {pages: [{ms, visits, timed_out}], questions: {key: {first_ms,
+// changes}}, back: [{from, to, at_ms}]}. Pages are numbered from 0; first_ms counts
+// from when the question's page was first shown, changes are the answer changes after
+// the first answer, at_ms counts from the start of the session.
+window.SQE.paradataTracker = function(pageCount, start) {
+    const data = {
+        pages: Array.from({ length: pageCount }, () => ({ ms: 0, visits: 0, timed_out: false })),
+        questions: {},
+        back: [],
+    };
+    const firstShownAt = new Array(pageCount).fill(null);
+    let current = 0, enteredAt = null;
+    return {
+        data,
+        // page `idx` is shown
+        enter(idx, now) {
+            if (enteredAt !== null) {
+                data.pages[current].ms += now - enteredAt;
+                if (idx < current) data.back.push({ from: current, to: idx, at_ms: now - start });
+            }
+            if (enteredAt === null || idx !== current) data.pages[idx].visits++;
+            enteredAt = now;
+            if (firstShownAt[idx] === null) firstShownAt[idx] = now;
+            current = idx;
+        },
+        // ms spent on page `idx`, the current visit included
+        spent(idx, now) {
+            return data.pages[idx].ms + (idx === current && enteredAt !== null ? now - enteredAt : 0);
+        },
+        // an input of question `key` on the current page changed
+        answer(key, now) {
+            const q = data.questions[key] = data.questions[key] || { first_ms: null, changes: 0 };
+            if (q.first_ms === null) q.first_ms = now - (firstShownAt[current] === null ? start : firstShownAt[current]);
+            else q.changes++;
+        },
+        snapshot(now) {
+            const out = JSON.parse(JSON.stringify(data));
+            if (enteredAt !== null) out.pages[current].ms += now - enteredAt;
+            return out;
+        },
+        // continue from the paradata of resumed progress
+        restore(saved, now) {
+            if (!saved || !Array.isArray(saved.pages)) return;
+            saved.pages.forEach((p, i) => { if (data.pages[i] && p) Object.assign(data.pages[i], p); });
+            Object.assign(data.questions, saved.questions || {});
+            if (Array.isArray(saved.back)) data.back = saved.back.concat(data.back);
+            if (enteredAt !== null) enteredAt = now;
+        },
+    };
+};"#;
+
+/// JSON value as a JS literal that is safe inside a `<script>` element.
+fn js_json(value: &Value) -> String {
+    value.to_string().replace("</", "<\\/")
+}
+
+/// Document start through `<body>`: meta tags, title and the shared stylesheet. Also
+/// used by other generated pages (reports) so they look like the questionnaire.
+pub(crate) fn write_head<W: Write>(f: &mut W, title: &str, lang: &str, extra_css: &str) -> io::Result<()> {
     writeln!(f, "<!doctype html>")?;
     writeln!(
         f,
-        "<html lang=\"en\"><head><meta charset=\"utf-8\"><meta name=\"viewport\" content=\"width=device-width,initial-scale=1\">"
+        "<html lang=\"{}\"><head><meta charset=\"utf-8\"><meta name=\"viewport\" content=\"width=device-width,initial-scale=1\">",
+        escape_html(lang)
     )?;
-    writeln!(f, "<title>{}</title>", escape_html(&doc_title))?;
+    writeln!(f, "<title>{}</title>", escape_html(title))?;
     writeln!(f, "<style>")?;
     writeln!(
         f,
@@ -69,10 +173,111 @@ pub fn build_pages(ast: &[Entry], out_dir: &str) -> io::Result<()> {
         ".page-indicator{{text-align:center;margin-top:12px;color:#666}}"
     )?;
     writeln!(f, "button:disabled{{opacity:.5;cursor:not-allowed}}")?;
+    writeln!(
+        f,
+        ".load-answers{{text-align:center;margin-top:16px;font-size:.9em}}.load-answers button{{margin:0 4px}}"
+    )?;
+    writeln!(f, ".required-mark{{color:#d93025}}")?;
+    writeln!(f, ".question.missing{{outline:2px solid #d93025}}")?;
+    writeln!(f, ".other-text{{margin-left:6px}}.other-text:disabled{{opacity:.5}}")?;
+    writeln!(f, ".progress{{height:8px;margin:8px 0 12px;border-radius:4px;background:#e8eaed;overflow:hidden}}.progress-fill{{height:100%;width:0;background:#1a73e8;transition:width .2s}}")?;
+    writeln!(f, ".toc{{margin-bottom:12px;font-size:.9em}}.toc ol{{margin:0;padding-left:20px}}.toc a{{color:#1a73e8;text-decoration:none}}")?;
+    writeln!(f, ".toc a.toc-current{{font-weight:bold;color:#333}}.toc a.toc-locked{{color:#999;cursor:default}}")?;
+    writeln!(f, ".toc-sidebar{{position:fixed;top:20px;left:20px;width:180px}}@media (max-width:1340px){{.toc-sidebar{{position:static;width:auto}}}}")?;
+    writeln!(f, ".page-timer{{position:sticky;top:0;padding:6px 10px;text-align:right;font-weight:bold;background:#fff}}.page-timer.timer-low{{color:#d93025}}")?;
+    writeln!(f, ".question.quiz-correct{{outline:2px solid #188038}}.question.quiz-incorrect{{outline:2px solid #d93025}}")?;
+    writeln!(f, ".quiz-answer label{{font-weight:bold}}.quiz-answer label::after{{content:\" \\2713\";color:#188038}}")?;
+    writeln!(f, ".quiz-feedback{{margin:2px 0 6px 24px;font-size:.9em;color:#555}}")?;
+    writeln!(f, ".table-downloads{{margin-top:8px;font-size:.9em}}")?;
+    writeln!(
+        f,
+        ".review-banner{{margin:12px 0;padding:10px;border-radius:6px;background:#eee;color:#333}}"
+    )?;
+    writeln!(
+        f,
+        ".resume-banner{{margin:12px 0;padding:10px;border-radius:6px;background:#e8f0fe;color:#1a3e72}}"
+    )?;
+    writeln!(
+        f,
+        ".submit-status{{text-align:center;margin-top:12px;padding:10px;border-radius:6px;background:#fff4e5;color:#8a4b00}}"
+    )?;
+    writeln!(f, ".results-table{{border-collapse:collapse;margin:8px auto}}")?;
+    writeln!(f, ".results-table th,.results-table td{{border:1px solid #ddd;padding:4px 10px}}")?;
+    f.write_all(extra_css.as_bytes())?;
     writeln!(f, "</style>")?;
     writeln!(f, "</head><body>")?;
+    Ok(())
+}
+
+pub fn build_pages(ast: &[Entry], out_dir: &str, options: &BuildOptions) -> io::Result<()> {
+    create_dir_all(out_dir)?;
+
+    // Collect document-level title (if any) and pages.
+    let mut pages: Vec<(String, &Vec<Question>)> = Vec::new();
+    let mut time_limits: Vec<Option<u32>> = Vec::new();
+    let mut doc_title_opt: Option<String> = None;
+    let mut submit: Option<&Submit> = None;
+    for entry in ast {
+        match entry {
+            Entry::DocTitle(t) => {
+                // first DocTitle wins; later ones overwrite previous
+                doc_title_opt = Some(t.clone());
+            }
+            Entry::Submit(sub) => {
+                // later submit directives override earlier ones
+                submit = Some(sub);
+            }
+            Entry::Page { title, time_limit, content } => {
+                pages.push((title.clone(), content));
+                time_limits.push(*time_limit);
+            }
+            _ => {}
+        }
+    }
+ 
+    let index_path = Path::new(out_dir).join("index.html");
+    let mut f = File::create(&index_path)?;
+ 
+    // Determine document title: prefer explicit DocTitle, else first page title, else fallback.
+    let doc_title = if let Some(ref t) = doc_title_opt {
+        t.clone()
+    } else if !pages.is_empty() {
+        pages[0].0.clone()
+    } else {
+        "Survey".to_string()
+    };
+ 
+    let lang = document_lang(ast);
+    write_head(&mut f, &doc_title, &lang, "")?;
 
     writeln!(f, "<h1>{}</h1>", escape_html(&doc_title))?;
+    let nav = NavigationSettings::of(ast);
+    if nav.progress.is_some() {
+        writeln!(f, "<div id=\"sqeProgress\" class=\"progress\" role=\"progressbar\" aria-valuemin=\"0\" aria-valuemax=\"100\"><div class=\"progress-fill\"></div></div>")?;
+    }
+    // contents from the page titles; the runtime keeps pages not yet reachable locked
+    let toc_title = |i: usize, title: &str| if title == "untitled" { format!("Page {}", i + 1) } else { title.to_string() };
+    match nav.toc {
+        Some(Toc::Sidebar) => {
+            writeln!(f, "<nav id=\"sqeToc\" class=\"toc toc-sidebar\" aria-label=\"Contents\"><ol>")?;
+            for (i, (title, _)) in pages.iter().enumerate() {
+                writeln!(f, "<li><a href=\"#\" data-page=\"{}\">{}</a></li>", i, escape_html(&toc_title(i, title)))?;
+            }
+            writeln!(f, "</ol></nav>")?;
+        }
+        Some(Toc::Dropdown) => {
+            writeln!(f, "<nav id=\"sqeToc\" class=\"toc toc-dropdown\"><label>Go to <select>")?;
+            for (i, (title, _)) in pages.iter().enumerate() {
+                writeln!(f, "<option value=\"{}\" data-page=\"{}\">{}</option>", i, i, escape_html(&toc_title(i, title)))?;
+            }
+            writeln!(f, "</select></label></nav>")?;
+        }
+        None => {}
+    }
+    if time_limits.iter().any(Option::is_some) {
+        // countdown of the current page's `timer`, filled in by the runtime
+        writeln!(f, "<div id=\"sqeTimer\" class=\"page-timer\" role=\"timer\" style=\"display:none;\"></div>")?;
+    }
     writeln!(f, "<div id=\"pages\">")?;
 
     // Collect per-page scripts as arrays of stringified objects (setup scripts and placeholders)
@@ -96,15 +301,18 @@ pub fn build_pages(ast: &[Entry], out_dir: &str) -> io::Result<()> {
                     let (html_frag, _maybe_js) = insert.render_html();
                     writeln!(f, "{}", html_frag)?;
                 }
-                Question::Choose(choose) => {
-                    let (html_frag, maybe_js) = choose.render_html(i, q_local_idx);
-                    writeln!(f, "{}", html_frag)?;
-                    if let Some(js) = maybe_js {
-                        // Wrap choose setup JS as an object with only "script"
-                        let obj = format!("{{\"script\":{}}}", to_js_string(&js));
-                        scripts_for_page.push(obj);
+                Question::Choose(_) | Question::Draw(_) => {
+                    // every question a draw may pick is rendered; the runtime hides the others
+                    for choose in q.choices() {
+                        let (html_frag, maybe_js) = choose.render_html(i, q_local_idx);
+                        writeln!(f, "{}", html_frag)?;
+                        if let Some(js) = maybe_js {
+                            // Wrap choose setup JS as an object with only "script"
+                            let obj = format!("{{\"script\":{}}}", to_js_string(&js));
+                            scripts_for_page.push(obj);
+                        }
+                        q_local_idx += 1;
                     }
-                    q_local_idx += 1;
                 }
                 Question::Html(node) => {
                     let (html_frag, _maybe_js) = node.render_html();
@@ -139,7 +347,41 @@ pub fn build_pages(ast: &[Entry], out_dir: &str) -> io::Result<()> {
     writeln!(f, "</div>")?;
     writeln!(f, "<div id=\"saveBtnContainer\" style=\"text-align:center; margin-top:20px; display:none;\">")?;
     writeln!(f, "<button id=\"saveBtn\">Save Answers</button>")?;
+    writeln!(
+        f,
+        "<div class=\"table-downloads\"><button type=\"button\" id=\"csvBtn\">Download CSV</button> <button type=\"button\" id=\"tsvBtn\">Download TSV</button></div>"
+    )?;
     writeln!(f, "</div>")?;
+    // load a previously saved answers.json, to resume or to review it read-only
+    writeln!(f, "<div id=\"loadAnswers\" class=\"load-answers\">")?;
+    writeln!(f, "<button type=\"button\" id=\"loadBtn\">Load answers</button>")?;
+    writeln!(f, "<button type=\"button\" id=\"reviewBtn\">Review answers</button>")?;
+    writeln!(
+        f,
+        "<input type=\"file\" id=\"loadFile\" accept=\".json,application/json\" style=\"display:none;\">"
+    )?;
+    writeln!(f, "</div>")?;
+    if submit.is_some() {
+        // submit progress / failure messages for the respondent
+        writeln!(f, "<div id=\"sqeSubmitStatus\" class=\"submit-status\" role=\"status\" style=\"display:none;\"></div>")?;
+        // shown by the runtime once the answers were accepted by the submit endpoint
+        writeln!(f, "<section id=\"sqeThanks\" class=\"thanks\" style=\"display:none;\">")?;
+        writeln!(f, "<h2>Thank you!</h2><p>Your answers have been submitted.</p>")?;
+        writeln!(f, "</section>")?;
+    }
+    let scorer = Scorer::new(ast);
+    if scorer.scales.iter().any(|s| s.feedback) {
+        // filled in by the runtime with the `feedback yes` scale scores once the answers are saved
+        writeln!(f, "<section id=\"sqeResults\" class=\"results\" style=\"display:none;\"><h2>Your results</h2><table class=\"results-table\"></table></section>")?;
+    }
+    // `mode quiz` grading data; with --hide-answers the page cannot grade itself
+    let quiz = grading::quiz(ast).filter(|_| !options.hide_answers).map(|q| {
+        json!({ "per_page": q.per_page, "reveal": q.reveal, "items": grading::runtime_json(ast) })
+    });
+    if quiz.is_some() {
+        // filled in by the runtime with the quiz score once the answers are saved
+        writeln!(f, "<section id=\"sqeQuiz\" class=\"results\" style=\"display:none;\"><h2>Your score</h2><p class=\"quiz-score\"></p></section>")?;
+    }
     writeln!(
         f,
         "<div class=\"page-indicator\" id=\"pageIndicator\"></div>"
@@ -147,6 +389,76 @@ pub fn build_pages(ast: &[Entry], out_dir: &str) -> io::Result<()> {
 
     writeln!(f, "<script>")?;
     writeln!(f, "const PAGE_COUNT = {};", pages.len())?;
+    writeln!(f, "const PAGE_TIME_LIMITS = {};", js_json(&json!(time_limits)))?;
+    // Questionnaire identity: used to key saved progress so answers never leak
+    // between questionnaires or between versions of the same questionnaire, and
+    // recorded with every saved or submitted response.
+    let id = json!({
+        "title": doc_title,
+        "hash": content_hash(ast),
+        "lang": lang,
+        "generator": {
+            "name": env!("CARGO_PKG_NAME"),
+            "version": env!("CARGO_PKG_VERSION"),
+            "commit": env!("GIT_COMMIT"),
+        },
+    });
+    writeln!(f, "const SQE_ID = {};", js_json(&id))?;
+    writeln!(f, "const SQE_LABELS = {};", js_json(&answer_labels(ast)))?;
+    // column order and multi-select separator for the CSV/TSV downloads (see `table`)
+    let table = json!({ "columns": answer_columns(ast), "multi_separator": multi_separator(ast) });
+    writeln!(f, "const SQE_TABLE = {};", js_json(&table))?;
+    // computed variables in evaluation order, compiled against the SQE.expr helpers
+    let known = input_keys(ast).into_iter().collect();
+    let ordered = evaluation_order(&computed(ast), &known).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    write!(f, "const SQE_COMPUTED = [")?;
+    for (i, c) in ordered.iter().enumerate() {
+        write!(f, "{}[{}, (A, X) => {}]", if i > 0 { "," } else { "" }, to_js_string(&c.name), c.expr.to_js())?;
+    }
+    writeln!(f, "];")?;
+    // `.show if` conditions: answer key, its unanswered value, condition
+    write!(f, "const SQE_CONDITIONS = [")?;
+    let mut first = true;
+    for (_, key, c) in choice_items(ast) {
+        if let Some(Ok(condition)) = c.condition() {
+            let empty = if c.is_multiple() { "[]" } else { "null" };
+            write!(f, "{}[{}, {}, (A, X) => {}]", if first { "" } else { "," }, to_js_string(&key), empty, condition.to_js())?;
+            first = false;
+        }
+    }
+    writeln!(f, "];")?;
+    // `draw` items: the key of the pick, how many to pick and [answer key, unanswered value] per question
+    let draws: Vec<Value> = draws(ast)
+        .into_iter()
+        .map(|(_, d)| {
+            let items: Vec<Value> = d
+                .items
+                .iter()
+                .map(|c| json!([c.id, if c.is_multiple() { json!([]) } else { Value::Null }]))
+                .collect();
+            json!({ "key": d.key, "count": d.count, "items": items })
+        })
+        .collect();
+    writeln!(f, "const SQE_DRAWS = {};", js_json(&Value::Array(draws)))?;
+    // missing-value codes per answer key: {key: {kind: code}}
+    let mut missing = serde_json::Map::new();
+    for (_, key, c) in choice_items(ast) {
+        let codes: serde_json::Map<String, Value> =
+            c.missing.iter().filter_map(|m| Some((m.kind.name().to_string(), m.value.as_ref()?.to_json()))).collect();
+        if !codes.is_empty() {
+            missing.insert(key, Value::Object(codes));
+        }
+    }
+    writeln!(f, "const SQE_MISSING = {};", js_json(&Value::Object(missing)))?;
+    // scales with item ranges and norm tables for SQE.scores() (see `scoring` and `norms`)
+    writeln!(f, "const SQE_SCALES = {};", js_json(&scorer.runtime_json()))?;
+    writeln!(f, "const SQE_QUIZ = {};", js_json(&json!(quiz)))?;
+    // `progress`, `toc` and `navigation free`
+    writeln!(f, "const SQE_NAV = {};", js_json(&nav.to_json()))?;
+    match submit {
+        Some(sub) => writeln!(f, "const SQE_SUBMIT = {};", sub.to_js_config())?,
+        None => writeln!(f, "const SQE_SUBMIT = null;")?,
+    }
 
     write!(f, "const PAGE_SCRIPTS = [")?;
     for (pidx, page) in page_scripts.iter().enumerate() {
@@ -173,12 +485,206 @@ pub fn build_pages(ast: &[Entry], out_dir: &str) -> io::Result<()> {
     }
     writeln!(f, "];")?;
 
+    writeln!(f, "{}", expr::RUNTIME_JS)?;
+    writeln!(f, "{}", DRAW_JS)?;
+    writeln!(f, "{}", PARADATA_JS)?;
+    writeln!(f, "Object.keys(SQE_MISSING).forEach(k => {{ window.SQE.expr.codes[k] = Object.values(SQE_MISSING[k]); }});")?;
+
     // Updated nav / runtime JS: defines SQE API and runs page scripts robustly (supports async and return values)
     let nav_js = r#"document.addEventListener("DOMContentLoaded", () => {
     // tiny runtime API for f { ... } scripts
     window.SQE = window.SQE || {};
     const SQE = window.SQE;
+ 
+    // Session info recorded with the answers. The seed drives SQE.random() and the
+    // `draw` picks so any randomisation can be reproduced from a saved response.
+    SQE.startedAt = new Date().toISOString();
+    SQE.seed = Math.floor(Math.random() * 4294967296);
+    let _sqeRandom = SQE.seededRandom(SQE.seed);
+    SQE.setSeed = function(seed) { SQE.seed = seed >>> 0; _sqeRandom = SQE.seededRandom(SQE.seed); };
+    SQE.random = function() { return _sqeRandom(); };
+
+    // Human-readable labels for the current answers: one label per single answer,
+    // a list for multi-select answers; keys without options are left out.
+    SQE.answerLabels = function(answers) {
+        const out = {};
+        answers = answers || window.SQE_ANSWERS || {};
+        Object.keys(answers).forEach(key => {
+            const opts = SQE_LABELS[key];
+            const val = answers[key];
+            if (!opts || val === null || typeof val === 'undefined') return;
+            const label = v => (String(v) in opts) ? opts[String(v)] : null;
+            out[key] = Array.isArray(val) ? val.map(label) : label(val);
+        });
+        return out;
+    };
+ 
+    // The answers wrapped with questionnaire identity and session metadata.
+    SQE.buildRecord = function() {
+        const answers = SQE.codeMissing(window.SQE_ANSWERS || {});
+        return {
+            meta: {
+                title: SQE_ID.title,
+                hash: SQE_ID.hash,
+                lang: SQE_ID.lang,
+                generator: SQE_ID.generator,
+                started_at: SQE.startedAt,
+                finished_at: new Date().toISOString(),
+                seed: SQE.seed,
+                paradata: SQE.paradata(),
+                url: location.href,
+                user_agent: navigator.userAgent,
+            },
+            answers: answers,
+            labels: SQE.answerLabels(answers),
+            quiz: SQE_QUIZ ? SQE.grade(answers) : undefined,
+        };
+    };
     
+    // Norm table row for a raw score, matched like `sqe-core score` does: `*` or empty
+    // cells match anything, `a-b` is an inclusive numeric range, otherwise equality.
+    function normCellMatches(cell, value) {
+        cell = String(cell).trim();
+        if (cell === "" || cell === "*") return true;
+        const x = typeof value === "number" ? value : (typeof value === "string" && value.trim() !== "" ? Number(value) : NaN);
+        if (!isNaN(x)) {
+            const dash = cell.indexOf("-", 1);
+            if (dash > 0) {
+                const lo = cell.slice(0, dash).trim(), hi = cell.slice(dash + 1).trim();
+                if (lo !== "" && hi !== "" && !isNaN(Number(lo)) && !isNaN(Number(hi))) return Number(lo) <= x && x <= Number(hi);
+            }
+            if (!isNaN(Number(cell))) return Number(cell) === x;
+        }
+        if (value === null || typeof value === "undefined") return false;
+        return String(value) === cell;
+    }
+    function normLookup(table, raw, answers) {
+        const col = name => table.columns.indexOf(name);
+        const row = table.rows.find(r => normCellMatches(r[col("raw")] || "", raw)
+            && table.by.every(k => col(k) >= 0 && normCellMatches(r[col(k)] || "", answers[k])));
+        if (!row) return null;
+        const get = name => { const c = col(name) >= 0 ? String(row[col(name)] || "").trim() : ""; return c === "" ? null : c; };
+        const num = name => { const v = get(name); return v === null || isNaN(Number(v)) ? null : Number(v); };
+        return { percentile: num("percentile"), t: num("t"), band: get("band") };
+    }
+    const answerNumber = v => {
+        if (typeof v === "number") return v;
+        if (typeof v === "boolean") return v ? 1 : 0;
+        if (typeof v === "string" && v.trim() !== "" && isFinite(Number(v))) return Number(v);
+        if (Array.isArray(v)) { if (v.length === 0) return null; const xs = v.map(answerNumber); return xs.includes(null) ? null : xs.reduce((a, b) => a + b, 0); }
+        return null;
+    };
+
+    // Scores of the declared scales for the current (or given) answers, computed the
+    // same way as `sqe-core score`: {name: {score, answered, items, percentile, t, band}}.
+    SQE.scores = function(answers) {
+        answers = answers || window.SQE_ANSWERS || {};
+        const out = {};
+        SQE_SCALES.forEach(scale => {
+            const values = scale.items.map(item => {
+                const v = SQE.missingKind(item.key, answers[item.key]) ? null : answerNumber(answers[item.key]);
+                if (v === null) return null;
+                return item.reversed && item.range ? item.range[0] + item.range[1] - v : v;
+            }).filter(v => v !== null);
+            const answered = values.length;
+            let score = null;
+            if (answered > 0 && answered >= scale.min_answered) {
+                const sum = values.reduce((a, b) => a + b, 0);
+                const mean = sum / answered;
+                score = scale.method === "mean" ? mean : scale.missing === "prorate" ? mean * scale.items.length : sum;
+            }
+            const result = { score, answered, items: scale.items.length };
+            if (scale.norms) Object.assign(result, { percentile: null, t: null, band: null }, score === null ? {} : normLookup(scale.norms, score, answers) || {});
+            out[scale.name] = result;
+        });
+        return out;
+    };
+
+    // Quiz grade (`mode quiz`) of the current (or given) answers, computed the same way as
+    // `sqe-core score`: {points, max, items: {key: {points, max, correct}}}. Only the
+    // questions in `keys` when given.
+    SQE.grade = function(answers, keys) {
+        answers = answers || window.SQE_ANSWERS || {};
+        const out = { points: 0, max: 0, items: {} };
+        if (!SQE_QUIZ) return out;
+        const undrawn = SQE.undrawnKeys(answers);
+        Object.keys(SQE_QUIZ.items).filter(k => (!keys || keys.includes(k)) && !undrawn.has(k)).forEach(key => {
+            const item = SQE_QUIZ.items[key];
+            const val = answers[key];
+            const chosen = Array.isArray(val)
+                ? item.options.map((o, i) => i).filter(i => val.some(v => SQE.expr.eq(item.options[i].value, v)))
+                : [item.options.findIndex(o => SQE.expr.eq(o.value, val))].filter(i => i >= 0);
+            const sum = chosen.reduce((s, i) => s + item.options[i].points, 0);
+            const correct = chosen.length > 0 && (item.multiple
+                ? item.options.every((o, i) => chosen.includes(i) === o.correct)
+                : item.options[chosen[0]].correct);
+            const points = item.multiple ? Math.max(sum, 0) : sum;
+            out.items[key] = { points, max: item.max, correct };
+            out.points += points;
+            out.max += item.max;
+        });
+        return out;
+    };
+
+    // Mark graded questions as correct or incorrect, show the feedback of the chosen
+    // options and, with `reveal`, the correct options. Graded questions are locked.
+    SQE.showFeedback = function(keys) {
+        if (!SQE_QUIZ) return;
+        const grade = SQE.grade(window.SQE_ANSWERS, keys);
+        Object.keys(grade.items).forEach(key => {
+            const item = SQE_QUIZ.items[key];
+            const inputs = Array.from(document.querySelectorAll('[data-sqe-key="' + String(key).replace(/"/g, '\\"') + '"]'));
+            const fs = inputs.length && inputs[0].closest ? inputs[0].closest('.question') : null;
+            if (!fs) return;
+            fs.classList.remove('quiz-correct', 'quiz-incorrect');
+            fs.classList.add(grade.items[key].correct ? 'quiz-correct' : 'quiz-incorrect');
+            fs.querySelectorAll('.quiz-feedback').forEach(el => el.remove());
+            inputs.forEach(el => {
+                el.disabled = true;
+                const opt = item.options.find(o => SQE.expr.eq(o.value, SQE.typedValue(el)));
+                const row = el.parentNode;
+                if (!opt || !row) return;
+                if (SQE_QUIZ.reveal && opt.correct) row.classList.add('quiz-answer');
+                if (el.checked && opt.feedback) {
+                    const div = document.createElement('div');
+                    div.className = 'quiz-feedback';
+                    div.textContent = opt.feedback;
+                    row.appendChild(div);
+                }
+            });
+        });
+    };
+
+    // The kind of missing-value code (`missing ...`) an answer is, or null.
+    SQE.missingKind = function(key, value) {
+      const codes = SQE_MISSING[key] || {};
+      return Object.keys(codes).find(kind => SQE.expr.eq(codes[kind], value)) || null;
+    };
+
+    // Answers as saved: an unanswered question gets its `not_applicable` code when a
+    // `.show if` condition hides it, else its `not_answered` code (where declared).
+    SQE.codeMissing = function(answers) {
+      const out = Object.assign({}, answers);
+      Object.keys(SQE_MISSING).forEach(key => {
+        if (SQE.expr.answered(out[key])) return;
+        const kind = SQE.hiddenKeys.has(key) ? "not_applicable" : "not_answered";
+        if (kind in SQE_MISSING[key]) out[key] = SQE_MISSING[key][kind];
+      });
+      return out;
+    };
+
+    // The answer value of an option input, typed as declared by its data-sqe-type
+    // (inputs without one keep the old guess: numeric strings become numbers).
+    SQE.typedValue = function(el) {
+      const raw = el.getAttribute('data-sqe-value') ?? el.value;
+      const type = el.getAttribute('data-sqe-type');
+      if (raw === null || type === 'string') return raw;
+      if (type === 'boolean') return raw === 'true';
+      const num = Number(raw);
+      if (type === 'integer' || type === 'number') return num;
+      return (Number.isFinite(num) && raw !== '') ? num : raw;
+    };
+
     // Collect answers from DOM inputs marked with data-sqe-key.
     // This lets user scripts read window.SQE_ANSWERS immediately without needing to manually gather values.
     // Improvements: support checkbox groups as arrays, robustly coerce numbers, and debug-log collected values.
@@ -195,24 +701,22 @@ pub fn build_pages(ast: &[Entry], out_dir: &str) -> io::Result<()> {
         const types = new Set(group.map(g => (g.type || g.tagName || '').toLowerCase()));
         // collect values
         if (types.has('checkbox')) {
-          // collect array of checked values
+          // collect array of checked values, typed like a single answer so that they
+          // match the option values listed in answers.schema.json
           const vals = [];
           group.forEach(g => {
             try {
-              if (g.checked) {
-                const v = g.getAttribute('data-sqe-value') ?? g.value;
-                vals.push(v);
-              }
+              if (g.checked) vals.push(SQE.typedValue(g));
             } catch(e){}
           });
-          window.SQE_ANSWERS[key] = vals;
-          console.debug("[SQE] collectAnswers:", key, "=", vals);
+          // a checked "don't know" / "refused" box stores its code instead of a list
+          const code = group.find(g => g.checked && g.getAttribute('data-sqe-missing'));
+          window.SQE_ANSWERS[key] = code ? SQE.typedValue(code) : vals;
+          console.debug("[SQE] collectAnswers:", key, "=", window.SQE_ANSWERS[key]);
         } else if (types.has('radio')) {
           // single value
-          let val = null;
-          group.forEach(g => { try { if (g.checked) val = g.getAttribute('data-sqe-value') ?? g.value; } catch(e){} });
-          const num = Number(val);
-          const final = (val === null) ? null : (Number.isFinite(num) && val !== '') ? num : val;
+          let final = null;
+          group.forEach(g => { try { if (g.checked) final = SQE.typedValue(g); } catch(e){} });
           window.SQE_ANSWERS[key] = final;
           console.debug("[SQE] collectAnswers:", key, "=", final);
         } else {
@@ -225,6 +729,83 @@ pub fn build_pages(ast: &[Entry], out_dir: &str) -> io::Result<()> {
           console.debug("[SQE] collectAnswers:", key, "=", final);
         }
       });
+      SQE.drawItems();
+      // hiding a question clears its answer, which may change other conditions
+      for (let pass = 0; pass < 5; pass++) {
+        SQE.syncOther();
+        SQE.computeVars();
+        if (!SQE.applyConditions()) break;
+      }
+      SQE.updatePipes();
+    };
+
+    // Enable the "other, please specify" text fields whose option is selected; the
+    // others are emptied and their answers cleared.
+    SQE.syncOther = function(answers) {
+      answers = answers || window.SQE_ANSWERS || {};
+      document.querySelectorAll('[data-sqe-other-for]').forEach(el => {
+        const opt = document.getElementById(el.getAttribute('data-sqe-other-for'));
+        el.disabled = !(opt && opt.checked);
+        if (el.disabled) {
+          el.value = '';
+          answers[el.getAttribute('data-sqe-key')] = null;
+        }
+      });
+    };
+
+    // Show or hide the questions with a `.show if` condition and the questions a `draw`
+    // did not pick. Hidden questions count as unanswered: their inputs are cleared and
+    // the answer is stored as null ([] for multiple choice) and their keys kept in
+    // SQE.hiddenKeys. Returns whether an answer was cleared.
+    SQE.hiddenKeys = new Set();
+    SQE.applyConditions = function(answers) {
+      answers = answers || window.SQE_ANSWERS || {};
+      let cleared = false;
+      SQE.hiddenKeys = new Set();
+      // answer key -> [unanswered value, shown]
+      const shown = new Map();
+      const undrawn = SQE.undrawnKeys(answers);
+      SQE_DRAWS.forEach(d => d.items.forEach(([key, empty]) => shown.set(key, [empty, !undrawn.has(key)])));
+      SQE_CONDITIONS.forEach(([key, empty, test]) => {
+        let show = true;
+        try { show = SQE.expr.truthy(test(answers, SQE.expr)); } catch(e) { console.error("[SQE] .show if for " + key, e); }
+        shown.set(key, [empty, show && !undrawn.has(key)]);
+      });
+      shown.forEach(([empty, show], key) => {
+        const inputs = Array.from(document.querySelectorAll('[data-sqe-key="' + String(key).replace(/"/g, '\\"') + '"]'));
+        const fs = inputs.length && inputs[0].closest ? inputs[0].closest('.question') : null;
+        if (fs) {
+          fs.style.display = show ? "" : "none";
+          if (show) fs.removeAttribute('data-sqe-hidden'); else fs.setAttribute('data-sqe-hidden', 'true');
+        }
+        if (show) return;
+        SQE.hiddenKeys.add(key);
+        inputs.forEach(el => { if (el.checked) { el.checked = false; cleared = true; } });
+        if (JSON.stringify(answers[key]) !== JSON.stringify(empty)) { answers[key] = empty; cleared = true; }
+      });
+      return cleared;
+    };
+
+    // Recalculate the computed variables (`let`) into the answers, in dependency order.
+    SQE.computeVars = function(answers) {
+      answers = answers || window.SQE_ANSWERS || {};
+      SQE_COMPUTED.forEach(([name, fn]) => {
+        try { answers[name] = fn(answers, SQE.expr); } catch(e) { answers[name] = null; console.error("[SQE] let " + name, e); }
+      });
+      return answers;
+    };
+
+    // Fill `{{key}}` placeholders with the current answer: the option label for choice
+    // answers, else the value itself.
+    SQE.updatePipes = function() {
+      const answers = window.SQE_ANSWERS || {};
+      const labels = SQE.answerLabels(answers);
+      document.querySelectorAll("[data-sqe-pipe]").forEach(el => {
+        const key = el.getAttribute("data-sqe-pipe");
+        let v = (key in labels && labels[key] !== null) ? labels[key] : answers[key];
+        if (Array.isArray(v)) v = v.filter(x => x !== null).join(", ");
+        el.textContent = (v === null || typeof v === "undefined") ? "" : String(v);
+      });
     };
     
     // Debounced runner to reduce excessive runs on rapid input events
@@ -295,6 +876,10 @@ pub fn build_pages(ast: &[Entry], out_dir: &str) -> io::Result<()> {
     
     let currentIndex = 0;
     const ran = new Array(PAGE_COUNT).fill(false);
+    // pages already graded in `mode quiz per-page`
+    const graded = new Array(PAGE_COUNT).fill(false);
+    // paradata saved with the answers (see SQE.paradata)
+    const paradata = SQE.paradataTracker(PAGE_COUNT, Date.now());
     
     // Run all scripts for a page (each script is an object { id: "...", script: "..." })
     function runScriptsForPage(idx, scriptsArray) {
@@ -400,6 +985,7 @@ pub fn build_pages(ast: &[Entry], out_dir: &str) -> io::Result<()> {
     function showPage(idx) {
         if (idx < 0) idx = 0;
         if (idx >= PAGE_COUNT) idx = PAGE_COUNT - 1;
+        paradata.enter(idx, Date.now());
         currentIndex = idx;
  
         pages.forEach((p, i) => {
@@ -415,9 +1001,10 @@ pub fn build_pages(ast: &[Entry], out_dir: &str) -> io::Result<()> {
         if (prevBtn) prevBtn.disabled = (idx === 0);
         if (nextBtn) nextBtn.disabled = (idx === PAGE_COUNT - 1);
         if (pageIndicator) pageIndicator.textContent = "Page " + (idx + 1) + " of " + PAGE_COUNT;
+        updateNav();
         const saveContainer = document.getElementById("saveBtnContainer");
         if (saveContainer) {
-            if (idx === PAGE_COUNT - 1) {
+            if (idx === PAGE_COUNT - 1 && !SQE.reviewMode) {
                 saveContainer.style.display = "block";
             } else {
                 saveContainer.style.display = "none";
@@ -433,40 +1020,666 @@ pub fn build_pages(ast: &[Entry], out_dir: &str) -> io::Result<()> {
         } catch (e) {
             console.error("Error running page scripts for page", idx + 1, e);
         }
+        startTimer();
+        document.dispatchEvent(new CustomEvent('sqe:page', { detail: { index: idx } }));
+    }
+
+    // Per-page time limits (`timer 90s`): a countdown that locks the page and moves on
+    // when it runs out. Time from earlier visits to the page counts against the limit.
+    const timerEl = document.getElementById("sqeTimer");
+    let timerTick = null;
+    function timeLeft(idx) {
+        if (!PAGE_TIME_LIMITS[idx]) return null;
+        return Math.max(0, PAGE_TIME_LIMITS[idx] * 1000 - paradata.spent(idx, Date.now()));
+    }
+    function updateTimer() {
+        const left = SQE.reviewMode ? null : timeLeft(currentIndex);
+        if (timerEl) timerEl.style.display = left === null ? "none" : "block";
+        if (left === null) return;
+        const s = Math.ceil(left / 1000);
+        if (timerEl) {
+            timerEl.textContent = left > 0 ? "Time left: " + Math.floor(s / 60) + ":" + String(s % 60).padStart(2, "0") : "Time is up";
+            timerEl.classList.toggle("timer-low", left > 0 && s <= 10);
+        }
+        if (left > 0) return;
+        if (timerTick) { clearInterval(timerTick); timerTick = null; }
+        const idx = currentIndex;
+        if (pages[idx]) pages[idx].querySelectorAll('input, select, textarea').forEach(el => { el.disabled = true; });
+        if (paradata.data.pages[idx].timed_out) return;
+        paradata.data.pages[idx].timed_out = true;
+        document.dispatchEvent(new CustomEvent('sqe:timeout', { detail: { index: idx } }));
+        // a page with quiz feedback stays until "Next", like a click on it
+        if (!gradePage(idx) && idx < PAGE_COUNT - 1) showPage(idx + 1);
     }
+    function startTimer() {
+        if (timerTick) { clearInterval(timerTick); timerTick = null; }
+        if (timeLeft(currentIndex) && !SQE.reviewMode) timerTick = setInterval(updateTimer, 250);
+        updateTimer();
+    }
+
+    // `toc` contents: pages already visited can be jumped to (any page with
+    // `navigation free`, or in review mode); `progress` bar over pages or required answers.
+    const tocEl = document.getElementById("sqeToc");
+    const progressEl = document.getElementById("sqeProgress");
+    function reachable(idx) {
+        return SQE.reviewMode || SQE_NAV.free || paradata.data.pages[idx].visits > 0;
+    }
+    function jumpTo(idx) {
+        if (idx === currentIndex || !(idx >= 0 && idx < PAGE_COUNT) || !reachable(idx)) return;
+        // moving forward is like "Next": chosen "other" options of the current page need
+        // their text (unless navigation is free), and a graded page first shows its feedback
+        if (idx > currentIndex && !SQE_NAV.free && !SQE.checkOtherText(currentIndex)) return;
+        if (idx > currentIndex && gradePage(currentIndex)) return;
+        showPage(idx);
+    }
+    function updateNav() {
+        if (tocEl) {
+            tocEl.querySelectorAll('[data-page]').forEach(el => {
+                const i = Number(el.getAttribute('data-page'));
+                const locked = i !== currentIndex && !reachable(i);
+                if (el.tagName === 'OPTION') {
+                    el.disabled = locked;
+                    el.selected = i === currentIndex;
+                } else {
+                    el.classList.toggle('toc-current', i === currentIndex);
+                    el.classList.toggle('toc-locked', locked);
+                    el.setAttribute('aria-disabled', String(locked));
+                    if (i === currentIndex) el.setAttribute('aria-current', 'page'); else el.removeAttribute('aria-current');
+                }
+            });
+        }
+        updateProgress();
+    }
+    function updateProgress() {
+        if (!progressEl) return;
+        // pages mode counts the pages before the current one as done
+        let done = currentIndex, total = PAGE_COUNT, text;
+        if (SQE_NAV.progress === 'required') {
+            const required = Array.from(document.querySelectorAll('#pages .question[data-sqe-required]')).filter(fs => !fs.hasAttribute('data-sqe-hidden'));
+            done = required.filter(hasAnswer).length;
+            total = required.length;
+            text = done + " of " + total + " required questions answered";
+        } else {
+            text = "Page " + (currentIndex + 1) + " of " + total;
+        }
+        const percent = total > 0 ? Math.round(done / total * 100) : 100;
+        progressEl.firstElementChild.style.width = percent + "%";
+        progressEl.setAttribute('aria-valuenow', String(percent));
+        progressEl.setAttribute('aria-valuetext', text);
+        progressEl.title = text;
+    }
+    if (tocEl) {
+        tocEl.addEventListener('click', e => {
+            const link = e.target && e.target.closest ? e.target.closest('a[data-page]') : null;
+            if (!link) return;
+            e.preventDefault();
+            jumpTo(Number(link.getAttribute('data-page')));
+        });
+        tocEl.addEventListener('change', e => {
+            if (e.target.tagName !== 'SELECT') return;
+            jumpTo(Number(e.target.value));
+            e.target.value = String(currentIndex);
+        });
+    }
+    if (progressEl && SQE_NAV.progress === 'required') {
+        document.addEventListener('change', updateProgress);
+        document.addEventListener('sqe:answer', updateProgress);
+    }
+
+    // Paradata so far (see SQE.paradataTracker for the format).
+    SQE.paradata = function() { return paradata.snapshot(Date.now()); };
+    SQE.restoreParadata = function(saved) { paradata.restore(saved, Date.now()); };
+    document.addEventListener('change', function(e) {
+        const key = e.target && e.target.getAttribute ? e.target.getAttribute('data-sqe-key') : null;
+        if (key && !SQE.reviewMode) paradata.answer(key, Date.now());
+    }, true);
+ 
+    // Put a saved answers map back into the inputs and SQE_ANSWERS, then re-run the
+    // function blocks so their output reflects the restored answers.
+    SQE.restoreAnswers = function(answers) {
+        answers = answers || {};
+        window.SQE_ANSWERS = Object.assign({}, answers);
+        // string options ("007") match only their exact text
+        const same = (el, a, b) => String(a) === String(b) || (el.getAttribute('data-sqe-type') !== 'string' && a !== '' && b !== '' && Number(a) === Number(b));
+        Object.keys(answers).forEach(key => {
+            const val = answers[key];
+            const group = document.querySelectorAll('[data-sqe-key="'+String(key).replace(/"/g,'\\"')+'"]');
+            group.forEach(el => {
+                const type = (el.type || '').toLowerCase();
+                const v = el.getAttribute('data-sqe-value') ?? el.value;
+                if (type === 'radio') {
+                    el.checked = val !== null && typeof val !== 'undefined' && same(el, v, val);
+                } else if (type === 'checkbox') {
+                    el.checked = Array.isArray(val) ? val.some(x => same(el, v, x)) : same(el, v, val);
+                } else if ('value' in el) {
+                    el.value = (val === null || typeof val === 'undefined') ? '' : String(val);
+                }
+            });
+        });
+        try { SQE.collectAnswers(); } catch(e) {}
+        // keep keys that have no input (e.g. set through SQE.setAnswer)
+        Object.keys(answers).forEach(k => { if (!(k in window.SQE_ANSWERS)) window.SQE_ANSWERS[k] = answers[k]; });
+        runAllFunctions();
+    };
+    SQE.runAllFunctions = runAllFunctions;
+ 
+    // Whether any input of a question holds an answer.
+    function hasAnswer(fs) {
+        return Array.from(fs.querySelectorAll('input, select, textarea')).some(el =>
+            (el.type === 'radio' || el.type === 'checkbox') ? el.checked : el.value !== '');
+    }
+    // Questions on a page whose chosen "other" option still lacks its text. `required`
+    // questions are only marked and listed in the codebook and schema, not enforced here.
+    SQE.missingOtherText = function(idx) {
+        const page = pages[idx];
+        if (!page) return [];
+        return Array.from(page.querySelectorAll('.question')).filter(fs => !fs.hasAttribute('data-sqe-hidden') &&
+            Array.from(fs.querySelectorAll('[data-sqe-other-for]')).some(el => !el.disabled && el.value.trim() === ''));
+    };
+    // Highlight the questions of a page whose chosen "other" option lacks its text; true when there are none.
+    SQE.checkOtherText = function(idx) {
+        // a page whose time ran out cannot be changed any more
+        if (SQE.reviewMode || !pages[idx] || paradata.data.pages[idx].timed_out) return true;
+        const missing = SQE.missingOtherText(idx);
+        pages[idx].querySelectorAll('.question.missing').forEach(el => el.classList.remove('missing'));
+        missing.forEach(el => el.classList.add('missing'));
+        if (missing.length && typeof missing[0].scrollIntoView === 'function') missing[0].scrollIntoView({ block: 'center' });
+        return missing.length === 0;
+    };
+    document.addEventListener('change', function(e) {
+        const fs = e.target && e.target.closest ? e.target.closest('.question.missing') : null;
+        if (fs) fs.classList.remove('missing');
+    });
  
     if (prevBtn) {
         prevBtn.addEventListener("click", () => { showPage(currentIndex - 1); });
     }
+    // `mode quiz per-page`: grade a page the first time it is left. True when it had
+    // graded questions, whose feedback is then shown instead of moving on.
+    function gradePage(idx) {
+        if (!SQE_QUIZ || !SQE_QUIZ.per_page || SQE.reviewMode || graded[idx] || !pages[idx]) return false;
+        const keys = Array.from(pages[idx].querySelectorAll('[data-sqe-key]'))
+            .map(el => el.getAttribute('data-sqe-key')).filter(k => k in SQE_QUIZ.items);
+        graded[idx] = true;
+        if (!keys.length) return false;
+        SQE.collectAnswers();
+        SQE.showFeedback(keys);
+        return true;
+    }
     if (nextBtn) {
-        nextBtn.addEventListener("click", () => { showPage(currentIndex + 1); });
+        nextBtn.addEventListener("click", () => {
+            if (!SQE.checkOtherText(currentIndex)) return;
+            // the first click on a graded page shows its feedback, the next one moves on
+            if (gradePage(currentIndex)) return;
+            showPage(currentIndex + 1);
+        });
     }
  
+    // navigation API (used e.g. by the preview server to keep the page across reloads)
+    SQE.showPage = showPage;
+    SQE.jumpTo = jumpTo;
+    SQE.currentPage = function() { return currentIndex; };
+ 
     showPage(0);
 });"#;
 
     writeln!(f, "{}", nav_js)?;
 
-    writeln!(f, "{}", r#"
+    // Loading saved answers: from a file picked with "Load answers"/"Review answers", or
+    // from `?answers=<url>` (add `&review` for read-only review mode). Accepts a bare
+    // answers map or a submitted payload with an `answers` field.
+    let load_js = r#"
+document.addEventListener("DOMContentLoaded", () => {
+    const SQE = window.SQE;
+    const params = new URLSearchParams(location.search);
+    const fileInput = document.getElementById("loadFile");
+    const loadBtn = document.getElementById("loadBtn");
+    const reviewBtn = document.getElementById("reviewBtn");
+    let reviewNext = false;
+ 
+    function answersOf(data) {
+        if (data && typeof data === "object" && !Array.isArray(data)) {
+            if (data.answers && typeof data.answers === "object" && !Array.isArray(data.answers)) return data.answers;
+            if (data.response && data.response.answers && typeof data.response.answers === "object") return data.response.answers;
+            return data;
+        }
+        throw new Error("expected a JSON object of answers");
+    }
+ 
+    // Read-only review: inputs are disabled, saving is hidden, navigation still works.
+    SQE.setReviewMode = function(on) {
+        SQE.reviewMode = !!on;
+        document.querySelectorAll('#pages input, #pages select, #pages textarea').forEach(el => { el.disabled = !!on; });
+        const save = document.getElementById("saveBtnContainer");
+        if (save && on) save.style.display = "none";
+        let banner = document.getElementById("sqeReviewBanner");
+        if (on && !banner) {
+            banner = document.createElement("div");
+            banner.id = "sqeReviewBanner";
+            banner.className = "review-banner";
+            banner.textContent = "Review mode: answers are shown read-only.";
+            const pagesEl = document.getElementById("pages");
+            if (pagesEl && pagesEl.parentNode) pagesEl.parentNode.insertBefore(banner, pagesEl);
+            else document.body.appendChild(banner);
+        } else if (!on && banner) {
+            banner.remove();
+        }
+        if (!on && typeof SQE.showPage === "function") SQE.showPage(SQE.currentPage());
+    };
+ 
+    SQE.loadAnswers = function(data, review) {
+        const meta = data && data.meta;
+        if (meta && meta.hash && meta.hash !== SQE_ID.hash) {
+            console.warn("[SQE] answers were saved from a different version of this questionnaire");
+        }
+        if (meta && typeof meta.seed === "number") SQE.setSeed(meta.seed);
+        SQE.restoreAnswers(answersOf(data));
+        if (review) SQE.setReviewMode(true);
+        if (typeof SQE.showPage === "function") SQE.showPage(0);
+    };
+ 
+    function pick(review) {
+        reviewNext = review;
+        if (fileInput) { fileInput.value = ""; fileInput.click(); }
+    }
+    if (loadBtn) loadBtn.addEventListener("click", () => pick(false));
+    if (reviewBtn) reviewBtn.addEventListener("click", () => pick(true));
+    if (fileInput) fileInput.addEventListener("change", () => {
+        const file = fileInput.files && fileInput.files[0];
+        if (!file) return;
+        file.text().then(text => SQE.loadAnswers(JSON.parse(text), reviewNext))
+            .catch(e => alert("Could not load answers: " + e.message));
+    });
+ 
+    const src = params.get("answers");
+    if (src) {
+        fetch(src, { cache: "no-store" })
+            .then(r => { if (!r.ok) throw new Error("HTTP " + r.status); return r.json(); })
+            .then(data => SQE.loadAnswers(data, params.has("review")))
+            .catch(e => alert("Could not load answers from " + src + ": " + e.message));
+    } else if (params.has("review")) {
+        SQE.setReviewMode(true);
+    }
+});
+"#;
+    writeln!(f, "{}", load_js)?;
+
+    // Autosave: keep answers and the current page in localStorage so a reload or a
+    // closed tab can be resumed. Cleared after a successful submit.
+    let autosave_js = r#"
+document.addEventListener("DOMContentLoaded", () => {
+    const SQE = window.SQE;
+    const KEY = "sqe:progress:" + SQE_ID.hash.slice(0, 16);
+    let restoring = false;
+    // true while the resume prompt is open: saving then would overwrite the progress
+    // the respondent has not yet chosen to resume or discard
+    let undecided = false;
+ 
+    function load() {
+        try { return JSON.parse(localStorage.getItem(KEY) || "null"); } catch(e) { return null; }
+    }
+    function save() {
+        if (restoring || undecided || SQE.reviewMode) return;
+        try {
+            localStorage.setItem(KEY, JSON.stringify({
+                title: SQE_ID.title,
+                answers: window.SQE_ANSWERS || {},
+                page: typeof SQE.currentPage === "function" ? SQE.currentPage() : 0,
+                started_at: SQE.startedAt,
+                seed: SQE.seed,
+                paradata: SQE.paradata(),
+                saved_at: new Date().toISOString(),
+            }));
+        } catch(e) { console.warn("[SQE] autosave failed", e); }
+    }
+    SQE.clearProgress = function() {
+        try { localStorage.removeItem(KEY); } catch(e) {}
+    };
+ 
+    let timer = null;
+    function saveSoon() {
+        if (timer) clearTimeout(timer);
+        timer = setTimeout(() => { timer = null; save(); }, 200);
+    }
+ 
+    const saved = load();
+    const hasAnswers = saved && saved.answers && Object.values(saved.answers).some(v =>
+        v !== null && typeof v !== "undefined" && v !== "" && !(Array.isArray(v) && v.length === 0));
+ 
+    // answers passed in the URL take precedence over saved progress
+    if (hasAnswers && !new URLSearchParams(location.search).has("answers")) {
+        undecided = true;
+        const banner = document.createElement("div");
+        banner.className = "resume-banner";
+        const when = saved.saved_at ? new Date(saved.saved_at).toLocaleString() : "";
+        const text = document.createElement("span");
+        text.textContent = "You have unfinished answers" + (when ? " from " + when : "") + ". ";
+        banner.appendChild(text);
+        const resume = document.createElement("button");
+        resume.type = "button";
+        resume.textContent = "Resume";
+        const restart = document.createElement("button");
+        restart.type = "button";
+        restart.textContent = "Start over";
+        restart.style.marginLeft = "8px";
+        banner.appendChild(resume);
+        banner.appendChild(restart);
+        const pagesEl = document.getElementById("pages");
+        if (pagesEl && pagesEl.parentNode) pagesEl.parentNode.insertBefore(banner, pagesEl);
+        else document.body.appendChild(banner);
+ 
+        resume.addEventListener("click", () => {
+            restoring = true;
+            try {
+                if (saved.started_at) SQE.startedAt = saved.started_at;
+                if (typeof saved.seed === "number") SQE.setSeed(saved.seed);
+                SQE.restoreParadata(saved.paradata);
+                SQE.restoreAnswers(saved.answers);
+                if (typeof SQE.showPage === "function") SQE.showPage(saved.page || 0);
+            } finally {
+                restoring = false;
+            }
+            undecided = false;
+            banner.remove();
+            save();
+        });
+        restart.addEventListener("click", () => {
+            undecided = false;
+            SQE.clearProgress();
+            banner.remove();
+            save();
+        });
+    }
+ 
+    document.addEventListener("input", saveSoon);
+    document.addEventListener("change", saveSoon);
+    document.addEventListener("sqe:answer", saveSoon);
+    document.addEventListener("sqe:page", saveSoon);
+});
+"#;
+    writeln!(f, "{}", autosave_js)?;
+
+    let save_js = r#"
 document.addEventListener("DOMContentLoaded", () => {
     const saveBtn = document.getElementById("saveBtn");
+    const statusEl = document.getElementById("sqeSubmitStatus");
+    // Unsent payloads are kept here until a send succeeds (one queue per endpoint).
+    const OUTBOX_KEY = SQE_SUBMIT ? "sqe:outbox:" + SQE_SUBMIT.url : null;
+ 
+    function downloadAnswers(data, name, type) {
+        const blob = new Blob([data], { type: type || "application/json" });
+        const url = URL.createObjectURL(blob);
+        const a = document.createElement("a");
+        a.href = url;
+        a.download = name || "answers.json";
+        document.body.appendChild(a);
+        a.click();
+        document.body.removeChild(a);
+        URL.revokeObjectURL(url);
+    }
+ 
+    // One-row table of the answers, columns in questionnaire order. Same layout as
+    // `sqe-core merge`: metadata columns first, then one column per answer key.
+    function answersTable(delimiter) {
+        const record = window.SQE.buildRecord();
+        const quote = v => /["\r\n]/.test(v) || v.includes(delimiter) ? '"' + v.replace(/"/g, '""') + '"' : v;
+        const cell = v => v === null || typeof v === "undefined" ? ""
+            : Array.isArray(v) ? v.map(cell).join(SQE_TABLE.multi_separator)
+            : typeof v === "string" ? v : JSON.stringify(v);
+        const columns = SQE_TABLE.columns.concat(Object.keys(record.answers).filter(k => !SQE_TABLE.columns.includes(k)));
+        const header = ["source", "id", "hash", "started_at", "finished_at"].concat(columns);
+        const values = ["answers", "", record.meta.hash, record.meta.started_at, record.meta.finished_at]
+            .concat(columns.map(k => cell(record.answers[k])));
+        return header.map(quote).join(delimiter) + "\n" + values.map(v => quote(cell(v))).join(delimiter) + "\n";
+    }
+    const csvBtn = document.getElementById("csvBtn");
+    const tsvBtn = document.getElementById("tsvBtn");
+    if (csvBtn) csvBtn.addEventListener("click", () => downloadAnswers(answersTable(","), "answers.csv", "text/csv"));
+    if (tsvBtn) tsvBtn.addEventListener("click", () => downloadAnswers(answersTable("\t"), "answers.tsv", "text/tab-separated-values"));
+ 
+    // Fill in and show the results screen for the `feedback yes` scales, if any.
+    function showResults() {
+        const section = document.getElementById("sqeResults");
+        if (!section) return;
+        const scores = window.SQE.scores();
+        const fmt = v => v === null || typeof v === "undefined" ? "–" : String(Math.round(v * 100) / 100);
+        const rows = SQE_SCALES.filter(s => s.feedback).map(s => {
+            const r = scores[s.name];
+            const cells = [s.name, fmt(r.score)];
+            if (s.norms) cells.push(fmt(r.percentile), fmt(r.t), r.band || "–");
+            else cells.push("", "", "");
+            return cells;
+        });
+        const table = section.querySelector("table");
+        table.innerHTML = "";
+        [["Scale", "Score", "Percentile", "T", "Band"]].concat(rows).forEach((cells, i) => {
+            const tr = document.createElement("tr");
+            cells.forEach(text => {
+                const td = document.createElement(i === 0 ? "th" : "td");
+                td.textContent = text;
+                tr.appendChild(td);
+            });
+            table.appendChild(tr);
+        });
+        section.style.display = "block";
+    }
+
+    // Fill in and show the quiz score (`mode quiz`) and mark every graded question.
+    function showQuiz() {
+        const section = document.getElementById("sqeQuiz");
+        if (!section || !SQE_QUIZ) return;
+        window.SQE.showFeedback();
+        const grade = window.SQE.grade();
+        const fmt = v => String(Math.round(v * 100) / 100);
+        const percent = grade.max > 0 ? " (" + Math.round(grade.points / grade.max * 100) + "%)" : "";
+        section.querySelector(".quiz-score").textContent = "Score: " + fmt(grade.points) + " of " + fmt(grade.max) + " points" + percent;
+        section.style.display = "block";
+    }
+
+    // Replace the questionnaire with the thank-you screen after a successful submit.
+    function showThanks() {
+        ["pages", "saveBtnContainer", "pageIndicator", "sqeSubmitStatus", "sqeToc", "sqeTimer", "sqeProgress"].forEach(id => {
+            const el = document.getElementById(id);
+            if (el) el.style.display = "none";
+        });
+        const controls = document.querySelector(".controls");
+        if (controls) controls.style.display = "none";
+        const thanks = document.getElementById("sqeThanks");
+        if (thanks) thanks.style.display = "block";
+        showResults();
+        showQuiz();
+    }
+ 
+    // Show a message to the respondent, optionally with action buttons [[label, fn], ...].
+    function showStatus(text, actions) {
+        if (!statusEl) return;
+        statusEl.textContent = text;
+        (actions || []).forEach(([label, fn]) => {
+            const b = document.createElement("button");
+            b.type = "button";
+            b.textContent = label;
+            b.style.marginLeft = "8px";
+            b.addEventListener("click", fn);
+            statusEl.appendChild(b);
+        });
+        statusEl.style.display = text ? "block" : "none";
+    }
+ 
+    function readOutbox() {
+        try { return JSON.parse(localStorage.getItem(OUTBOX_KEY) || "[]"); } catch(e) { return []; }
+    }
+    function writeOutbox(items) {
+        try {
+            if (items.length) localStorage.setItem(OUTBOX_KEY, JSON.stringify(items));
+            else localStorage.removeItem(OUTBOX_KEY);
+        } catch(e) { console.error("[SQE] cannot write to localStorage", e); }
+    }
+ 
+    // Answers wrapped with metadata: the body sent to the endpoint and the contents
+    // of the downloaded answers.json.
+    function buildPayload() {
+        return window.SQE.buildRecord();
+    }
+ 
+    // One request; resolves on a 2xx response, rejects otherwise.
+    function sendOnce(body) {
+        const headers = Object.assign({ "Content-Type": "application/json" }, SQE_SUBMIT.headers || {});
+        return fetch(SQE_SUBMIT.url, { method: SQE_SUBMIT.method || "POST", headers, body })
+            .then(res => { if (!res.ok) throw new Error("HTTP " + res.status); return res; });
+    }
+ 
+    // Send with exponential backoff between attempts.
+    function sendWithRetry(body) {
+        const retries = SQE_SUBMIT.retries || 0;
+        const attempt = (n) => sendOnce(body).catch(e => {
+            if (n >= retries) throw e;
+            const delay = 500 * Math.pow(2, n);
+            return new Promise(r => setTimeout(r, delay)).then(() => attempt(n + 1));
+        });
+        return attempt(0);
+    }
+ 
+    // Try to deliver queued payloads, oldest first; stops at the first failure.
+    function flushOutbox() {
+        if (!SQE_SUBMIT || !SQE_SUBMIT.queue || navigator.onLine === false) return Promise.resolve(false);
+        const items = readOutbox();
+        if (!items.length) return Promise.resolve(true);
+        return sendWithRetry(items[0]).then(() => {
+            writeOutbox(readOutbox().slice(1));
+            return flushOutbox();
+        }).catch(e => { console.warn("[SQE] queued answers not sent yet", e); return false; });
+    }
+ 
+    function failed(body, err) {
+        console.error("[SQE] submit failed", err);
+        // the full record, the same file the page saves without a submit endpoint
+        const download = ["Download answers", () => downloadAnswers(body)];
+        if (SQE_SUBMIT.queue) {
+            const items = readOutbox();
+            if (!items.includes(body)) { items.push(body); writeOutbox(items); }
+            showStatus("Your answers could not be sent. They are saved on this device and will be sent automatically when the connection is back.",
+                [["Try again", () => submitAnswers(body)], download]);
+        } else {
+            showStatus("Your answers could not be sent (" + (err && err.message ? err.message : "network error") + "). They were downloaded as a file instead.",
+                [["Try again", () => submitAnswers(body)], download]);
+            download[1]();
+        }
+    }
+ 
+    function submitAnswers(body) {
+        if (navigator.onLine === false) { failed(body, new Error("offline")); return; }
+        if (saveBtn) saveBtn.disabled = true;
+        showStatus("Sending your answers…");
+        sendWithRetry(body).then(() => {
+            if (SQE_SUBMIT.queue) writeOutbox(readOutbox().filter(b => b !== body));
+            if (window.SQE && typeof window.SQE.clearProgress === "function") window.SQE.clearProgress();
+            showStatus("");
+            showThanks();
+        }).catch(e => failed(body, e)).finally(() => {
+            if (saveBtn) saveBtn.disabled = false;
+        });
+    }
+ 
     if (saveBtn) {
         saveBtn.addEventListener("click", () => {
-            const data = JSON.stringify(window.SQE_ANSWERS || {}, null, 2);
-            const blob = new Blob([data], { type: "application/json" });
-            const url = URL.createObjectURL(blob);
-            const a = document.createElement("a");
-            a.href = url;
-            a.download = "answers.json";
-            document.body.appendChild(a);
-            a.click();
-            document.body.removeChild(a);
-            URL.revokeObjectURL(url);
+            // go to the first page with a chosen "other" option that lacks its text
+            for (let i = 0; i < PAGE_COUNT; i++) {
+                if (!window.SQE.checkOtherText(i)) {
+                    if (window.SQE.currentPage() !== i) { window.SQE.showPage(i); window.SQE.checkOtherText(i); }
+                    return;
+                }
+            }
+            // Without a submit endpoint the answers are downloaded as a file.
+            if (!SQE_SUBMIT) {
+                downloadAnswers(JSON.stringify(buildPayload(), null, 2));
+                showResults();
+                showQuiz();
+                return;
+            }
+            submitAnswers(JSON.stringify(buildPayload()));
         });
     }
+ 
+    if (SQE_SUBMIT && SQE_SUBMIT.queue) {
+        window.addEventListener("online", () => { flushOutbox(); });
+        flushOutbox();
+    }
 });
-"#)?;
+"#;
+    writeln!(f, "{}", save_js)?;
     writeln!(f, "</script>")?;
     writeln!(f, "</body></html>")?;
+
+    // Schema of the answers.json this page saves, for validating collected files.
+    let schema = serde_json::to_string_pretty(&answers_schema(ast))?;
+    fs::write(Path::new(out_dir).join(SCHEMA_FILE), schema + "\n")?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draws_are_reproducible_from_the_seed() {
+        let items: Vec<Value> = (1..=10).map(|i| json!([format!("q{}", i), null])).collect();
+        let draws = json!([{ "key": "exam_draw", "count": 3, "items": items }]);
+        let script = format!(
+            "const window = globalThis;\n{}\nconst SQE_DRAWS = {};\n\
+             const pick = (seed, answers) => {{ window.SQE.seed = seed; answers = answers || {{}}; window.SQE.drawItems(answers); return answers.exam_draw; }};\n\
+             console.log(JSON.stringify([pick(7), pick(7), pick(8), pick(4000000000), pick(9, {{ exam_draw: ['q2', 'q4', 'q6'] }})]));",
+            DRAW_JS, draws
+        );
+        let output = std::process::Command::new("node")
+            .arg("-e")
+            .arg(&script)
+            .output()
+            .expect("node is needed to run the page runtime in this test");
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let picks: Vec<Vec<String>> = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(picks[0], picks[1]);
+        assert!(picks[..4].iter().all(|p| p.len() == 3 && p.windows(2).all(|w| w[0][1..].parse::<u32>().unwrap() < w[1][1..].parse::<u32>().unwrap())));
+        assert!(picks[1..4].iter().any(|p| *p != picks[0]));
+        // a saved pick is kept
+        assert_eq!(picks[4], vec!["q2", "q4", "q6"]);
+    }
+
+    #[test]
+    fn records_paradata() {
+        // pages 0 -> 1 -> 0 -> 1 with one answer on page 0 changed twice
+        let script = format!(
+            "const window = globalThis;\n{}\n\
+             const t = window.SQE.paradataTracker(3, 1000);\n\
+             t.enter(0, 1000); t.answer('a', 1400); t.answer('a', 1500); t.enter(1, 3000);\n\
+             t.answer('b', 3250); t.enter(0, 4000); t.answer('a', 4100); t.enter(1, 4500);\n\
+             const before = t.snapshot(5000);\n\
+             const resumed = window.SQE.paradataTracker(3, 9000); resumed.enter(0, 9000); resumed.restore(before, 9500);\n\
+             resumed.enter(1, 9700);\n\
+             console.log(JSON.stringify([before, t.spent(1, 5000), resumed.snapshot(9800)]));",
+            PARADATA_JS
+        );
+        let output = std::process::Command::new("node")
+            .arg("-e")
+            .arg(&script)
+            .output()
+            .expect("node is needed to run the page runtime in this test");
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let out: Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(
+            out[0],
+            json!({
+                "pages": [
+                    { "ms": 2500, "visits": 2, "timed_out": false },
+                    { "ms": 1500, "visits": 2, "timed_out": false },
+                    { "ms": 0, "visits": 0, "timed_out": false },
+                ],
+                "questions": { "a": { "first_ms": 400, "changes": 2 }, "b": { "first_ms": 250, "changes": 0 } },
+                "back": [{ "from": 1, "to": 0, "at_ms": 3000 }],
+            })
+        );
+        assert_eq!(out[1], json!(1500));
+        // resuming keeps the saved times and drops the time before the resume
+        assert_eq!((out[2]["pages"][0]["ms"].clone(), out[2]["pages"][1]["ms"].clone()), (json!(2700), json!(1600)));
+        assert_eq!(out[2]["back"].as_array().unwrap().len(), 1);
+    }
+}
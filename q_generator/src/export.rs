@@ -0,0 +1,343 @@
+// === src/export.rs ===
+
+use std::collections::{HashMap, HashSet};
+
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+
+use crate::items::scale::{Method, Missing};
+use crate::expr::Type;
+use crate::items::computed::evaluation_order;
+use crate::items::navigation::NavigationSettings;
+use crate::items::value::ValueType;
+use crate::items::{Choose, Computed, Draw, Scale};
+use crate::lint::attr_values;
+use crate::table::DEFAULT_MULTI_SEPARATOR;
+use crate::transcompiler::{Entry, Question};
+
+/// Language used when the questionnaire has no `lang` directive.
+pub const DEFAULT_LANG: &str = "en";
+
+/// Document title: the explicit `title` directive, else the first page title, else "Survey".
+/// Mirrors the rule used by `convert::build_pages`.
+pub fn document_title(ast: &[Entry]) -> String {
+    let mut doc_title: Option<String> = None;
+    let mut first_page: Option<String> = None;
+    for entry in ast {
+        match entry {
+            Entry::DocTitle(t) => doc_title = Some(t.clone()),
+            Entry::Page { title, .. } if first_page.is_none() => first_page = Some(title.clone()),
+            _ => {}
+        }
+    }
+    doc_title.or(first_page).unwrap_or_else(|| "Survey".to_string())
+}
+
+/// Document language from the `lang` directive (last one wins), else [`DEFAULT_LANG`].
+pub fn document_lang(ast: &[Entry]) -> String {
+    ast.iter()
+        .filter_map(|e| match e {
+            Entry::Lang(l) => Some(l.clone()),
+            _ => None,
+        })
+        .next_back()
+        .unwrap_or_else(|| DEFAULT_LANG.to_string())
+}
+
+/// Separator for multi-select answers in CSV/TSV output (last `multi-separator` wins).
+pub fn multi_separator(ast: &[Entry]) -> String {
+    ast.iter()
+        .filter_map(|e| match e {
+            Entry::MultiSeparator(s) => Some(s.clone()),
+            _ => None,
+        })
+        .next_back()
+        .unwrap_or_else(|| DEFAULT_MULTI_SEPARATOR.to_string())
+}
+
+/// Answer keys in questionnaire order (a draw's pick before its questions), then
+/// computed variables: the columns of tabular answer output.
+pub fn answer_columns(ast: &[Entry]) -> Vec<String> {
+    let mut columns: Vec<String> = Vec::new();
+    let draws = draws(ast);
+    for (_, key, c) in choice_items(ast) {
+        if let Some((_, d)) = draws.iter().find(|(_, d)| d.item_keys().first() == Some(&key.as_str())) {
+            columns.push(d.key.clone());
+        }
+        let other = c.other_keys(&key);
+        columns.push(key);
+        columns.extend(other.into_iter().map(|(_, k)| k));
+    }
+    columns.extend(computed(ast).into_iter().map(|c| c.name.clone()));
+    columns
+}
+
+/// Declared scales in source order.
+pub fn scales(ast: &[Entry]) -> Vec<&Scale> {
+    ast.iter()
+        .filter_map(|e| match e {
+            Entry::Scale(s) => Some(s),
+            _ => None,
+        })
+        .collect()
+}
+
+/// `draw` items in questionnaire order as `(page index, draw)`.
+pub fn draws(ast: &[Entry]) -> Vec<(usize, &Draw)> {
+    let pages = ast.iter().filter_map(|e| match e {
+        Entry::Page { content, .. } => Some(content),
+        _ => None,
+    });
+    pages
+        .enumerate()
+        .flat_map(|(page_idx, content)| {
+            content.iter().filter_map(move |q| match q {
+                Question::Draw(d) => Some((page_idx, d.as_ref())),
+                _ => None,
+            })
+        })
+        .collect()
+}
+
+/// Computed variables (`let`) in source order.
+pub fn computed(ast: &[Entry]) -> Vec<&Computed> {
+    ast.iter()
+        .filter_map(|e| match e {
+            Entry::Let(c) => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// "Other, please specify" text fields in questionnaire order as `(question key,
+/// option label, text key)`.
+pub fn other_fields(ast: &[Entry]) -> Vec<(String, &str, String)> {
+    choice_items(ast)
+        .into_iter()
+        .flat_map(|(_, key, c)| {
+            c.other_keys(&key).into_iter().map(move |(i, other)| (key.clone(), c.options[i].0.as_str(), other))
+        })
+        .collect()
+}
+
+/// Keys the page stores answers under: choice questions, their "other" text fields,
+/// the picks of `draw` items and `data-sqe-key` inputs in `html` blocks.
+pub fn input_keys(ast: &[Entry]) -> Vec<String> {
+    let mut keys: Vec<String> = choice_items(ast).into_iter().map(|(_, key, _)| key).collect();
+    keys.extend(other_fields(ast).into_iter().map(|(_, _, other)| other));
+    keys.extend(draws(ast).into_iter().map(|(_, d)| d.key.clone()));
+    for entry in ast {
+        if let Entry::Page { content, .. } = entry {
+            for q in content {
+                if let Question::Html(node) = q {
+                    keys.extend(attr_values(&node.html, "data-sqe-key").into_iter().filter(|k| !keys.contains(k)).collect::<Vec<_>>());
+                }
+            }
+        }
+    }
+    keys
+}
+
+/// Type of the answer a choice question stores: a list for multiple choice, else the
+/// type of its option values.
+pub fn answer_type(c: &Choose) -> Type {
+    if c.is_multiple() {
+        return Type::List;
+    }
+    match c.check_values() {
+        Ok(ValueType::Integer | ValueType::Number) => Type::Number,
+        Ok(ValueType::Boolean) => Type::Bool,
+        Ok(ValueType::String) => Type::String,
+        Err(_) => Type::Any,
+    }
+}
+
+/// Check every expression of the questionnaire: computed variables and `.show if`
+/// conditions may only read answer keys and computed variables, computed variables
+/// may not depend on each other in a cycle, and all of them must type check.
+pub fn check_expressions(ast: &[Entry]) -> Result<(), String> {
+    let known: HashSet<String> = input_keys(ast).into_iter().collect();
+    let mut types: HashMap<String, Type> = choice_items(ast).into_iter().map(|(_, k, c)| (k, answer_type(c))).collect();
+    types.extend(other_fields(ast).into_iter().map(|(_, _, other)| (other, Type::String)));
+    types.extend(draws(ast).into_iter().map(|(_, d)| (d.key.clone(), Type::List)));
+    for c in evaluation_order(&computed(ast), &known)? {
+        let t = c.expr.check(&|n| types.get(n).copied()).map_err(|e| format!("let {}: {}", c.name, e))?;
+        types.insert(c.name.clone(), t);
+    }
+    for (_, key, c) in choice_items(ast) {
+        let Some(condition) = c.condition() else { continue };
+        let condition = condition.map_err(|e| format!("{}: .show if: {}", key, e))?;
+        if let Some(name) = condition.references().into_iter().find(|r| !known.contains(*r) && !types.contains_key(*r)) {
+            return Err(format!("{}: .show if: `{}` is not an answer key or computed variable", key, name));
+        }
+        condition.check(&|n| types.get(n).copied()).map_err(|e| format!("{}: .show if: {}", key, e))?;
+    }
+    Ok(())
+}
+
+/// Every choice question in questionnaire order as `(page index, answer key, question)`,
+/// including every question a `draw` may pick.
+pub fn choice_items(ast: &[Entry]) -> Vec<(usize, String, &Choose)> {
+    let mut items = Vec::new();
+    let pages = ast.iter().filter_map(|e| match e {
+        Entry::Page { content, .. } => Some(content),
+        _ => None,
+    });
+    for (page_idx, content) in pages.enumerate() {
+        let choices = content.iter().flat_map(Question::choices);
+        for (q_idx, c) in choices.enumerate() {
+            items.push((page_idx, c.store_key(page_idx, q_idx), c));
+        }
+    }
+    items
+}
+
+/// Option labels per answer key (`{key: {value: label}}`), used to store readable
+/// labels next to the raw answer values.
+pub fn answer_labels(ast: &[Entry]) -> Value {
+    let mut labels = serde_json::Map::new();
+    for (_, key, c) in choice_items(ast) {
+        let mut options: serde_json::Map<String, Value> =
+            c.options.iter().map(|(label, value)| (value.to_string(), json!(label))).collect();
+        for m in &c.missing {
+            if let Some(value) = &m.value {
+                options.insert(value.to_string(), json!(m.label()));
+            }
+        }
+        labels.insert(key, Value::Object(options));
+    }
+    Value::Object(labels)
+}
+
+/// Machine-readable description of a compiled questionnaire: pages, items and the
+/// answer keys the runtime will store.
+pub fn questionnaire_json(ast: &[Entry]) -> Value {
+    let mut imports = Vec::new();
+    let mut submit = None;
+    let mut quiz = None;
+    let mut pages = Vec::new();
+    let mut page_idx = 0usize;
+
+    for entry in ast {
+        match entry {
+            Entry::Import { path } => imports.push(json!(path)),
+            Entry::DocTitle(_) | Entry::Lang(_) | Entry::MultiSeparator(_) | Entry::Scale(_) | Entry::Let(_) => {}
+            // document-level codes are part of each question's resolved `missing`
+            Entry::Missing(_) => {}
+            // banks are exported through the draws that use them
+            Entry::Bank(_) => {}
+            // exported together, see below
+            Entry::Navigation(_) => {}
+            Entry::Quiz(q) => quiz = Some(json!({ "per_page": q.per_page, "reveal": q.reveal })),
+            Entry::Submit(sub) => {
+                // header values may hold credentials, so only their names are exported
+                submit = Some(json!({
+                    "url": sub.url,
+                    "method": sub.method,
+                    "headers": sub.headers.iter().map(|(k, _)| k).collect::<Vec<_>>(),
+                    "retries": sub.retries,
+                    "queue": sub.queue,
+                }));
+            }
+            Entry::Page { title, time_limit, content } => {
+                let mut items = Vec::new();
+                let mut q_local_idx = 0usize;
+                for q in content {
+                    let item = match q {
+                        Question::Choose(c) => {
+                            q_local_idx += 1;
+                            choice_json(c, &c.store_key(page_idx, q_local_idx - 1))
+                        }
+                        Question::Draw(d) => {
+                            let items: Vec<Value> = d
+                                .items
+                                .iter()
+                                .map(|c| {
+                                    q_local_idx += 1;
+                                    choice_json(c, &c.store_key(page_idx, q_local_idx - 1))
+                                })
+                                .collect();
+                            json!({
+                                "type": "draw",
+                                "key": d.key,
+                                "count": d.count,
+                                "bank": d.bank,
+                                "where": d.filter,
+                                "items": items,
+                            })
+                        }
+                        Question::Insert(n) => json!({ "type": "insert", "text": n.text }),
+                        Question::Html(n) => json!({ "type": "html", "html": n.html }),
+                        Question::Js(n) => json!({ "type": "js", "script": n.script }),
+                        Question::Css(n) => json!({ "type": "css", "css": n.css }),
+                    };
+                    items.push(item);
+                }
+                pages.push(json!({ "title": title, "time_limit": time_limit, "items": items }));
+                page_idx += 1;
+            }
+        }
+    }
+
+    json!({
+        "title": document_title(ast),
+        "lang": document_lang(ast),
+        "multi_separator": multi_separator(ast),
+        "imports": imports,
+        "submit": submit,
+        "quiz": quiz,
+        "navigation": NavigationSettings::of(ast).to_json(),
+        "pages": pages,
+        "scales": scales(ast).iter().map(|s| json!({
+            "name": s.name,
+            "items": s.items.iter().map(|i| json!({ "key": i.key, "reversed": i.reversed })).collect::<Vec<_>>(),
+            "method": if s.method == Method::Mean { "mean" } else { "sum" },
+            "missing": if s.missing == Missing::Prorate { "prorate" } else { "skip" },
+            "min_answered": s.min_answered,
+            "norms": s.norms_source.as_ref().map(|(file, by)| json!({ "file": file, "by": by })),
+            "feedback": s.feedback,
+        })).collect::<Vec<_>>(),
+        "computed": computed(ast).iter().map(|c| json!({ "name": c.name, "expression": c.source })).collect::<Vec<_>>(),
+    })
+}
+
+/// A choice question as exported by [`questionnaire_json`].
+fn choice_json(c: &Choose, key: &str) -> Value {
+    let other = c.other_keys(key);
+    json!({
+        "type": "choice",
+        "id": c.id,
+        "key": key,
+        "question": c.question,
+        "options": c.options.iter().enumerate().map(|(i, (label, value))| json!({
+            "label": label,
+            "value": value.to_json(),
+            "other_key": other.iter().find(|(o, _)| *o == i).map(|(_, k)| k),
+            "correct": c.marks[i].correct,
+            "points": c.marks[i].points,
+            "feedback": c.marks[i].feedback,
+        })).collect::<Vec<_>>(),
+        "value_type": c.check_values().ok().map(|t| t.name()),
+        "missing": c.missing.iter().map(|m| json!({
+            "kind": m.kind.name(),
+            "label": m.label(),
+            "value": m.value.as_ref().map(|v| v.to_json()),
+        })).collect::<Vec<_>>(),
+        "tags": c.tags.iter().map(|(t, v)| (t.clone(), v.to_json())).collect::<serde_json::Map<_, _>>(),
+        "required": c.is_required(),
+        "multiple": c.is_multiple(),
+        "addons": c.addons,
+        "show_if": c.show_if,
+    })
+}
+
+/// SHA-256 (hex) of the questionnaire structure. Identifies a questionnaire version:
+/// any change to pages, questions, options or scripts changes the hash.
+pub fn content_hash(ast: &[Entry]) -> String {
+    // serde_json keeps object keys sorted, so the serialization is canonical
+    let canonical = questionnaire_json(ast).to_string();
+    Sha256::digest(canonical.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
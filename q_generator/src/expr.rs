@@ -0,0 +1,950 @@
+// === src/expr.rs ===
+
+//! Small typed expression language over answers, used by computed variables (`let`)
+//! and question conditions (`.show if`). Expressions are parsed and type checked once
+//! in Rust; [`Expr::eval`] evaluates them offline and [`Expr::to_js`] compiles them for
+//! the generated runtime, which provides the `SQE.expr` helpers the compiled code
+//! calls. The two evaluators are cross-tested against each other.
+//!
+//! ```text
+//! let bmi = round(weight / (height / 100) ^ 2, 1)
+//! let age = years_since(dob)
+//! let adult = age >= 18 and not (consent == "no")
+//! let symptoms = count(q5) + count(headache == 1, fever == 1)
+//! .show if smoker in [1, 2] and answered(cigarettes)
+//! ```
+//!
+//! Missing answers are `null`; arithmetic and ordering with `null` give `null`, so a
+//! computed value stays empty until everything it needs was answered. `sum` skips
+//! missing values instead. Answers holding a missing-value code of their question
+//! (`.missing refused >> -99`) read as `null` too, see [`Expr::eval_with_codes`].
+
+use std::collections::HashMap;
+
+use serde_json::{Value, json};
+
+use crate::items::common::to_js_string;
+use crate::items::missing::without_codes;
+use crate::items::value::OptionValue;
+use crate::responses::Answers;
+use crate::scoring::number;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+    /// Membership: `x in [1, 2]`, `3 in multi_answer`
+    In,
+}
+
+impl BinOp {
+    fn helper(self) -> &'static str {
+        match self {
+            BinOp::Add => "add",
+            BinOp::Sub => "sub",
+            BinOp::Mul => "mul",
+            BinOp::Div => "div",
+            BinOp::Rem => "rem",
+            BinOp::Pow => "pow",
+            BinOp::Eq => "eq",
+            BinOp::Ne => "ne",
+            BinOp::Lt => "lt",
+            BinOp::Le => "le",
+            BinOp::Gt => "gt",
+            BinOp::Ge => "ge",
+            BinOp::And => "and",
+            BinOp::Or => "or",
+            BinOp::In => "isin",
+        }
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            BinOp::Add => "+",
+            BinOp::Sub => "-",
+            BinOp::Mul => "*",
+            BinOp::Div => "/",
+            BinOp::Rem => "%",
+            BinOp::Pow => "^",
+            BinOp::Eq => "==",
+            BinOp::Ne => "!=",
+            BinOp::Lt => "<",
+            BinOp::Le => "<=",
+            BinOp::Gt => ">",
+            BinOp::Ge => ">=",
+            BinOp::And => "and",
+            BinOp::Or => "or",
+            BinOp::In => "in",
+        }
+    }
+}
+
+/// Static type of an expression. `Any` is used where the type is only known at run
+/// time (inputs in `html` blocks, `null`) and is compatible with every other type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Type {
+    Number,
+    String,
+    Bool,
+    List,
+    Any,
+}
+
+impl Type {
+    pub fn name(self) -> &'static str {
+        match self {
+            Type::Number => "number",
+            Type::String => "string",
+            Type::Bool => "boolean",
+            Type::List => "list",
+            Type::Any => "any",
+        }
+    }
+
+    /// The common type of two compatible types.
+    fn unify(self, other: Type) -> Option<Type> {
+        match (self, other) {
+            (Type::Any, t) | (t, Type::Any) => Some(t),
+            (a, b) if a == b => Some(a),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Literal(Value),
+    /// Answer key or computed variable
+    Var(String),
+    Neg(Box<Expr>),
+    Not(Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+    /// List literal `[a, b, c]`
+    List(Vec<Expr>),
+}
+
+/// Built-in functions with their (min, max) argument counts.
+const FUNCTIONS: &[(&str, usize, usize)] = &[
+    ("round", 1, 2),
+    ("floor", 1, 1),
+    ("ceil", 1, 1),
+    ("abs", 1, 1),
+    ("min", 1, usize::MAX),
+    ("max", 1, usize::MAX),
+    ("if", 3, 3),
+    ("coalesce", 1, usize::MAX),
+    ("years_since", 1, 1),
+    ("count", 1, usize::MAX),
+    ("sum", 1, usize::MAX),
+    ("answered", 1, 1),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Str(String),
+    Ident(String),
+    Op(&'static str),
+}
+
+fn tokenize(src: &str) -> Result<Vec<(usize, Token)>, String> {
+    const OPS: &[&str] = &["==", "!=", "<=", ">=", "&&", "||", "<", ">", "+", "-", "*", "/", "%", "^", "!", "(", ")", "[", "]", ","];
+    let mut tokens = Vec::new();
+    let mut rest = src;
+    while let Some(c) = rest.chars().next() {
+        let pos = src.len() - rest.len();
+        if c.is_whitespace() {
+            rest = &rest[c.len_utf8()..];
+        } else if c.is_ascii_digit() || (c == '.' && rest[1..].starts_with(|d: char| d.is_ascii_digit())) {
+            let end = rest.find(|d: char| !(d.is_ascii_digit() || d == '.')).unwrap_or(rest.len());
+            let n = rest[..end].parse::<f64>().map_err(|_| format!("invalid number `{}` at {}", &rest[..end], pos + 1))?;
+            tokens.push((pos, Token::Number(n)));
+            rest = &rest[end..];
+        } else if c == '"' || c == '\'' {
+            let mut text = String::new();
+            let mut chars = rest[1..].char_indices();
+            let mut end = None;
+            while let Some((i, ch)) = chars.next() {
+                match ch {
+                    '\\' => text.extend(chars.next().map(|(_, e)| e)),
+                    _ if ch == c => {
+                        end = Some(i + 2);
+                        break;
+                    }
+                    _ => text.push(ch),
+                }
+            }
+            let end = end.ok_or_else(|| format!("unterminated string at {}", pos + 1))?;
+            tokens.push((pos, Token::Str(text)));
+            rest = &rest[end..];
+        } else if c.is_alphabetic() || c == '_' {
+            let end = rest.find(|d: char| !(d.is_alphanumeric() || d == '_')).unwrap_or(rest.len());
+            tokens.push((pos, Token::Ident(rest[..end].to_string())));
+            rest = &rest[end..];
+        } else {
+            let op = OPS
+                .iter()
+                .find(|op| rest.starts_with(**op))
+                .ok_or_else(|| format!("unexpected `{}` at {}", c, pos + 1))?;
+            tokens.push((pos, Token::Op(op)));
+            rest = &rest[op.len()..];
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    len: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, t)| t)
+    }
+
+    fn at(&self) -> usize {
+        self.tokens.get(self.pos).map(|(p, _)| p + 1).unwrap_or(self.len + 1)
+    }
+
+    fn eat_op(&mut self, op: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Op(o)) if *o == op) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_word(&mut self, word: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Ident(w)) if w == word) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut left = self.and()?;
+        while self.eat_word("or") || self.eat_op("||") {
+            left = Expr::Binary(BinOp::Or, Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut left = self.not()?;
+        while self.eat_word("and") || self.eat_op("&&") {
+            left = Expr::Binary(BinOp::And, Box::new(left), Box::new(self.not()?));
+        }
+        Ok(left)
+    }
+
+    fn not(&mut self) -> Result<Expr, String> {
+        if self.eat_word("not") || self.eat_op("!") {
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let left = self.additive()?;
+        let ops = [("==", BinOp::Eq), ("!=", BinOp::Ne), ("<=", BinOp::Le), (">=", BinOp::Ge), ("<", BinOp::Lt), (">", BinOp::Gt)];
+        for (text, op) in ops {
+            if self.eat_op(text) {
+                return Ok(Expr::Binary(op, Box::new(left), Box::new(self.additive()?)));
+            }
+        }
+        if self.eat_word("in") {
+            return Ok(Expr::Binary(BinOp::In, Box::new(left), Box::new(self.additive()?)));
+        }
+        Ok(left)
+    }
+
+    fn additive(&mut self) -> Result<Expr, String> {
+        let mut left = self.multiplicative()?;
+        loop {
+            let op = if self.eat_op("+") {
+                BinOp::Add
+            } else if self.eat_op("-") {
+                BinOp::Sub
+            } else {
+                return Ok(left);
+            };
+            left = Expr::Binary(op, Box::new(left), Box::new(self.multiplicative()?));
+        }
+    }
+
+    fn multiplicative(&mut self) -> Result<Expr, String> {
+        let mut left = self.unary()?;
+        loop {
+            let op = if self.eat_op("*") {
+                BinOp::Mul
+            } else if self.eat_op("/") {
+                BinOp::Div
+            } else if self.eat_op("%") {
+                BinOp::Rem
+            } else {
+                return Ok(left);
+            };
+            left = Expr::Binary(op, Box::new(left), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat_op("-") {
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        let base = self.primary()?;
+        if self.eat_op("^") {
+            // right associative, binds tighter than unary minus on its left: -2^2 == -4
+            return Ok(Expr::Binary(BinOp::Pow, Box::new(base), Box::new(self.unary()?)));
+        }
+        Ok(base)
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        let at = self.at();
+        let token = self.tokens.get(self.pos).map(|(_, t)| t.clone());
+        self.pos += 1;
+        match token {
+            Some(Token::Number(n)) => Ok(Expr::Literal(number(n))),
+            Some(Token::Str(s)) => Ok(Expr::Literal(json!(s))),
+            Some(Token::Op("(")) => {
+                let inner = self.or()?;
+                if !self.eat_op(")") {
+                    return Err(format!("expected `)` at {}", self.at()));
+                }
+                Ok(inner)
+            }
+            Some(Token::Op("[")) => {
+                let mut items = Vec::new();
+                if !self.eat_op("]") {
+                    loop {
+                        items.push(self.or()?);
+                        if self.eat_op("]") {
+                            break;
+                        }
+                        if !self.eat_op(",") {
+                            return Err(format!("expected `,` or `]` at {}", self.at()));
+                        }
+                    }
+                }
+                Ok(Expr::List(items))
+            }
+            Some(Token::Ident(word)) => match word.as_str() {
+                "true" => Ok(Expr::Literal(json!(true))),
+                "false" => Ok(Expr::Literal(json!(false))),
+                "null" => Ok(Expr::Literal(Value::Null)),
+                "and" | "or" | "not" | "in" => Err(format!("unexpected `{}` at {}", word, at)),
+                _ if self.eat_op("(") => {
+                    let Some((_, min, max)) = FUNCTIONS.iter().find(|(name, _, _)| *name == word) else {
+                        return Err(format!("unknown function `{}` at {}", word, at));
+                    };
+                    let mut args = Vec::new();
+                    if !self.eat_op(")") {
+                        loop {
+                            args.push(self.or()?);
+                            if self.eat_op(")") {
+                                break;
+                            }
+                            if !self.eat_op(",") {
+                                return Err(format!("expected `,` or `)` at {}", self.at()));
+                            }
+                        }
+                    }
+                    if args.len() < *min || args.len() > *max {
+                        return Err(format!("wrong number of arguments for `{}` at {}", word, at));
+                    }
+                    Ok(Expr::Call(word, args))
+                }
+                _ => Ok(Expr::Var(word)),
+            },
+            Some(Token::Op(op)) => Err(format!("unexpected `{}` at {}", op, at)),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+}
+
+/// Parse an expression; errors name the 1-based column.
+pub fn parse(src: &str) -> Result<Expr, String> {
+    let mut parser = Parser {
+        tokens: tokenize(src)?,
+        pos: 0,
+        len: src.len(),
+    };
+    let expr = parser.or()?;
+    if parser.pos < parser.tokens.len() {
+        return Err(format!("unexpected input at {}", parser.at()));
+    }
+    Ok(expr)
+}
+
+/// Truthiness shared with the runtime: `null`, `false`, `0`, `""` and `[]` are false.
+pub fn truthy(v: &Value) -> bool {
+    match v {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().is_some_and(|x| x != 0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(a) => !a.is_empty(),
+        Value::Object(_) => true,
+    }
+}
+
+/// An answer that counts as given: not `null`, not `""`, not an empty selection.
+fn answered(v: &Value) -> bool {
+    match v {
+        Value::Null => false,
+        Value::String(s) => !s.is_empty(),
+        Value::Array(a) => !a.is_empty(),
+        _ => true,
+    }
+}
+
+/// `a in b`: whether the list `b` contains `a` (any element of `a` when `a` is a list).
+fn member(a: &Value, b: &Value) -> Value {
+    let Value::Array(list) = b else { return json!(false) };
+    match a {
+        Value::Null => json!(false),
+        Value::Array(xs) => json!(xs.iter().any(|x| list.iter().any(|y| equal(x, y)))),
+        x => json!(list.iter().any(|y| equal(x, y))),
+    }
+}
+
+/// Numeric view of a value: numbers and numeric strings.
+fn num(v: &Value) -> Option<f64> {
+    match v {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse::<f64>().ok().filter(|x| x.is_finite() && !s.trim().is_empty()),
+        Value::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+        _ => None,
+    }
+}
+
+fn num_result(x: f64) -> Value {
+    if x.is_finite() { number(x) } else { Value::Null }
+}
+
+fn equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(_), _) | (_, Value::Number(_)) => match (num(a), num(b)) {
+            (Some(x), Some(y)) => x == y,
+            _ => false,
+        },
+        _ => a == b,
+    }
+}
+
+/// Date part of an ISO date or timestamp (`YYYY-MM-DD...`).
+fn date(v: &Value) -> Option<chrono::NaiveDate> {
+    let s = v.as_str()?.trim();
+    chrono::NaiveDate::parse_from_str(s.get(..10)?, "%Y-%m-%d").ok()
+}
+
+fn years_since(v: &Value) -> Value {
+    use chrono::Datelike;
+    let Some(d) = date(v) else { return Value::Null };
+    let today = chrono::Local::now().date_naive();
+    let mut years = today.year() - d.year();
+    if (today.month(), today.day()) < (d.month(), d.day()) {
+        years -= 1;
+    }
+    json!(years)
+}
+
+impl Expr {
+    /// Answer keys and variables the expression reads, in order of appearance.
+    pub fn references(&self) -> Vec<&str> {
+        let mut out = Vec::new();
+        self.collect_refs(&mut out);
+        out
+    }
+
+    fn collect_refs<'a>(&'a self, out: &mut Vec<&'a str>) {
+        match self {
+            Expr::Literal(_) => {}
+            Expr::Var(name) => {
+                if !out.contains(&name.as_str()) {
+                    out.push(name);
+                }
+            }
+            Expr::Neg(e) | Expr::Not(e) => e.collect_refs(out),
+            Expr::Binary(_, a, b) => {
+                a.collect_refs(out);
+                b.collect_refs(out);
+            }
+            Expr::Call(_, args) | Expr::List(args) => args.iter().for_each(|a| a.collect_refs(out)),
+        }
+    }
+
+    /// Type check against the types of the variables it reads (unknown names are
+    /// `Any`; undefined references are reported separately). Returns the result type.
+    pub fn check(&self, var_type: &dyn Fn(&str) -> Option<Type>) -> Result<Type, String> {
+        let expect = |e: &Expr, want: Type, what: &str| -> Result<(), String> {
+            let t = e.check(var_type)?;
+            match t.unify(want) {
+                Some(_) => Ok(()),
+                None => Err(format!("{} needs a {}, not a {} ({})", what, want.name(), t.name(), e)),
+            }
+        };
+        Ok(match self {
+            Expr::Literal(Value::Number(_)) => Type::Number,
+            Expr::Literal(Value::String(_)) => Type::String,
+            Expr::Literal(Value::Bool(_)) => Type::Bool,
+            Expr::Literal(_) => Type::Any,
+            Expr::Var(name) => var_type(name).unwrap_or(Type::Any),
+            Expr::Neg(e) => {
+                expect(e, Type::Number, "`-`")?;
+                Type::Number
+            }
+            Expr::Not(e) => {
+                e.check(var_type)?;
+                Type::Bool
+            }
+            Expr::List(items) => {
+                let mut element = Type::Any;
+                for item in items {
+                    let t = item.check(var_type)?;
+                    element = element
+                        .unify(t)
+                        .ok_or_else(|| format!("list mixes {} and {} values ({})", element.name(), t.name(), self))?;
+                }
+                Type::List
+            }
+            Expr::Binary(op, a, b) => match op {
+                BinOp::And | BinOp::Or => {
+                    a.check(var_type)?;
+                    b.check(var_type)?;
+                    Type::Bool
+                }
+                BinOp::Eq | BinOp::Ne | BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => {
+                    let (ta, tb) = (a.check(var_type)?, b.check(var_type)?);
+                    if ta.unify(tb).is_none() {
+                        return Err(format!("`{}` compares a {} with a {} ({})", op.symbol(), ta.name(), tb.name(), self));
+                    }
+                    let ordered = !matches!(op, BinOp::Eq | BinOp::Ne);
+                    if ordered && matches!(ta.unify(tb), Some(Type::Bool | Type::List)) {
+                        return Err(format!("`{}` cannot order {} values ({})", op.symbol(), ta.unify(tb).unwrap_or(Type::Any).name(), self));
+                    }
+                    Type::Bool
+                }
+                BinOp::In => {
+                    a.check(var_type)?;
+                    expect(b, Type::List, "`in`")?;
+                    Type::Bool
+                }
+                _ => {
+                    let what = format!("`{}`", op.symbol());
+                    expect(a, Type::Number, &what)?;
+                    expect(b, Type::Number, &what)?;
+                    Type::Number
+                }
+            },
+            Expr::Call(name, args) => {
+                let what = format!("`{}`", name);
+                match name.as_str() {
+                    "if" => {
+                        args[0].check(var_type)?;
+                        let (ta, tb) = (args[1].check(var_type)?, args[2].check(var_type)?);
+                        ta.unify(tb)
+                            .ok_or_else(|| format!("`if` branches are a {} and a {} ({})", ta.name(), tb.name(), self))?
+                    }
+                    "coalesce" => {
+                        let mut t = Type::Any;
+                        for a in args {
+                            let ta = a.check(var_type)?;
+                            t = t.unify(ta).ok_or_else(|| format!("`coalesce` mixes {} and {} ({})", t.name(), ta.name(), self))?;
+                        }
+                        t
+                    }
+                    "years_since" => {
+                        expect(&args[0], Type::String, &what)?;
+                        Type::Number
+                    }
+                    "count" => {
+                        for a in args {
+                            a.check(var_type)?;
+                        }
+                        Type::Number
+                    }
+                    "answered" => {
+                        args[0].check(var_type)?;
+                        Type::Bool
+                    }
+                    "sum" => {
+                        for a in args {
+                            let t = a.check(var_type)?;
+                            if !matches!(t, Type::Number | Type::List | Type::Any) {
+                                return Err(format!("`sum` needs numbers or lists, not a {} ({})", t.name(), a));
+                            }
+                        }
+                        Type::Number
+                    }
+                    _ => {
+                        for a in args {
+                            expect(a, Type::Number, &what)?;
+                        }
+                        Type::Number
+                    }
+                }
+            }
+        })
+    }
+
+    /// Evaluate like the page runtime (`X.get`): an answer equal to one of its
+    /// question's missing-value codes (`codes` per answer key, as resolved by
+    /// `missing::resolve`) reads as `null`.
+    pub fn eval_with_codes(&self, answers: &Answers, codes: &HashMap<String, Vec<OptionValue>>) -> Value {
+        self.eval(&without_codes(codes, answers))
+    }
+
+    /// Evaluate against answers (missing keys are `null`).
+    pub fn eval(&self, answers: &Answers) -> Value {
+        match self {
+            Expr::Literal(v) => v.clone(),
+            Expr::Var(name) => answers.get(name).cloned().unwrap_or(Value::Null),
+            Expr::List(items) => Value::Array(items.iter().map(|e| e.eval(answers)).collect()),
+            Expr::Neg(e) => num(&e.eval(answers)).map(|x| num_result(-x)).unwrap_or(Value::Null),
+            Expr::Not(e) => json!(!truthy(&e.eval(answers))),
+            Expr::Binary(BinOp::And, a, b) => json!(truthy(&a.eval(answers)) && truthy(&b.eval(answers))),
+            Expr::Binary(BinOp::Or, a, b) => json!(truthy(&a.eval(answers)) || truthy(&b.eval(answers))),
+            Expr::Binary(op, a, b) => {
+                let (a, b) = (a.eval(answers), b.eval(answers));
+                match op {
+                    BinOp::In => return member(&a, &b),
+                    BinOp::Eq => return json!(equal(&a, &b)),
+                    BinOp::Ne => return json!(!equal(&a, &b)),
+                    BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => {
+                        let ordering = match (num(&a), num(&b), &a, &b) {
+                            (Some(x), Some(y), _, _) => x.partial_cmp(&y),
+                            (_, _, Value::String(x), Value::String(y)) => Some(x.cmp(y)),
+                            _ => None,
+                        };
+                        return ordering
+                            .map(|o| {
+                                json!(match op {
+                                    BinOp::Lt => o.is_lt(),
+                                    BinOp::Le => o.is_le(),
+                                    BinOp::Gt => o.is_gt(),
+                                    _ => o.is_ge(),
+                                })
+                            })
+                            .unwrap_or(Value::Null);
+                    }
+                    _ => {}
+                }
+                let (Some(x), Some(y)) = (num(&a), num(&b)) else { return Value::Null };
+                num_result(match op {
+                    BinOp::Add => x + y,
+                    BinOp::Sub => x - y,
+                    BinOp::Mul => x * y,
+                    BinOp::Div if y == 0.0 => return Value::Null,
+                    BinOp::Div => x / y,
+                    BinOp::Rem if y == 0.0 => return Value::Null,
+                    BinOp::Rem => x % y,
+                    _ => x.powf(y),
+                })
+            }
+            Expr::Call(name, args) => {
+                let arg = |i: usize| args.get(i).map(|a| a.eval(answers)).unwrap_or(Value::Null);
+                match name.as_str() {
+                    "if" => {
+                        if truthy(&arg(0)) { arg(1) } else { arg(2) }
+                    }
+                    "coalesce" => args.iter().map(|a| a.eval(answers)).find(|v| !v.is_null()).unwrap_or(Value::Null),
+                    "years_since" => years_since(&arg(0)),
+                    "answered" => json!(answered(&arg(0))),
+                    "count" => json!(args.iter().map(|a| match a.eval(answers) {
+                        Value::Array(items) => items.len(),
+                        v => usize::from(truthy(&v)),
+                    }).sum::<usize>()),
+                    "sum" => {
+                        let values: Vec<f64> = args
+                            .iter()
+                            .flat_map(|a| match a.eval(answers) {
+                                Value::Array(items) => items,
+                                v => vec![v],
+                            })
+                            .filter_map(|v| num(&v))
+                            .collect();
+                        if values.is_empty() { Value::Null } else { num_result(values.iter().sum()) }
+                    }
+                    "min" | "max" => {
+                        let values: Option<Vec<f64>> = args.iter().map(|a| num(&a.eval(answers))).collect();
+                        let values = values.unwrap_or_default();
+                        let pick = if name == "min" { f64::min } else { f64::max };
+                        values.into_iter().reduce(pick).map(num_result).unwrap_or(Value::Null)
+                    }
+                    "round" => {
+                        let (Some(x), digits) = (num(&arg(0)), num(&arg(1)).unwrap_or(0.0)) else { return Value::Null };
+                        let scale = 10f64.powi(digits as i32);
+                        // half away from zero, like the runtime's helper
+                        num_result(((x * scale).abs() + 0.5).floor().copysign(x) / scale)
+                    }
+                    _ => {
+                        let Some(x) = num(&arg(0)) else { return Value::Null };
+                        num_result(match name.as_str() {
+                            "floor" => x.floor(),
+                            "ceil" => x.ceil(),
+                            _ => x.abs(),
+                        })
+                    }
+                }
+            }
+        }
+    }
+
+    /// Compile to a JS expression over the answers object `A`, using the `SQE.expr`
+    /// runtime helpers bound to `X`.
+    pub fn to_js(&self) -> String {
+        match self {
+            Expr::Literal(v) => to_js_string_value(v),
+            Expr::Var(name) => format!("X.get(A,{})", to_js_string(name)),
+            Expr::Neg(e) => format!("X.neg({})", e.to_js()),
+            Expr::Not(e) => format!("!X.truthy({})", e.to_js()),
+            Expr::Binary(BinOp::And, a, b) => format!("(X.truthy({})&&X.truthy({}))", a.to_js(), b.to_js()),
+            Expr::Binary(BinOp::Or, a, b) => format!("(X.truthy({})||X.truthy({}))", a.to_js(), b.to_js()),
+            Expr::Binary(op, a, b) => format!("X.{}({},{})", op.helper(), a.to_js(), b.to_js()),
+            Expr::List(items) => format!("[{}]", items.iter().map(Expr::to_js).collect::<Vec<_>>().join(",")),
+            Expr::Call(name, args) if name == "if" => {
+                format!("(X.truthy({})?{}:{})", args[0].to_js(), args[1].to_js(), args[2].to_js())
+            }
+            Expr::Call(name, args) => {
+                let args: Vec<String> = args.iter().map(Expr::to_js).collect();
+                format!("X.{}({})", name, args.join(","))
+            }
+        }
+    }
+}
+
+/// Source-like rendering, used in type errors.
+impl std::fmt::Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let list = |items: &[Expr]| items.iter().map(|e| e.to_string()).collect::<Vec<_>>().join(", ");
+        match self {
+            Expr::Literal(v) => write!(f, "{}", v),
+            Expr::Var(name) => write!(f, "{}", name),
+            Expr::Neg(e) => write!(f, "-{}", e),
+            Expr::Not(e) => write!(f, "not {}", e),
+            Expr::Binary(op, a, b) => write!(f, "{} {} {}", a, op.symbol(), b),
+            Expr::Call(name, args) => write!(f, "{}({})", name, list(args)),
+            Expr::List(items) => write!(f, "[{}]", list(items)),
+        }
+    }
+}
+
+fn to_js_string_value(v: &Value) -> String {
+    match v {
+        Value::String(s) => to_js_string(s),
+        other => other.to_string(),
+    }
+}
+
+/// Runtime helpers for compiled expressions (`SQE.expr`). Must agree with [`Expr::eval`].
+pub const RUNTIME_JS: &str = r#"window.SQE = window.SQE || {};
+window.SQE.expr = (function() {
+    const num = v => typeof v === "number" ? (isFinite(v) ? v : null)
+        : typeof v === "boolean" ? (v ? 1 : 0)
+        : typeof v === "string" && v.trim() !== "" && isFinite(Number(v)) ? Number(v) : null;
+    const res = x => isFinite(x) ? x : null;
+    const arith = f => (a, b) => { const x = num(a), y = num(b); return x === null || y === null ? null : f(x, y); };
+    const cmp = f => (a, b) => {
+        const x = num(a), y = num(b);
+        if (x !== null && y !== null) return f(x < y ? -1 : x > y ? 1 : 0);
+        if (typeof a === "string" && typeof b === "string") return f(a < b ? -1 : a > b ? 1 : 0);
+        return null;
+    };
+    const eq = (a, b) => {
+        if (a === undefined) a = null;
+        if (b === undefined) b = null;
+        if (typeof a === "number" || typeof b === "number") { const x = num(a), y = num(b); return x !== null && y !== null && x === y; }
+        return JSON.stringify(a) === JSON.stringify(b);
+    };
+    const answered = v => !(v === null || v === undefined || v === "" || (Array.isArray(v) && v.length === 0));
+    const truthy = v => !(v === null || v === undefined || v === false || v === 0 || v === "" || (Array.isArray(v) && v.length === 0) || (typeof v === "number" && isNaN(v)));
+    return {
+        // missing-value codes per answer key (set by the page); they read as unanswered
+        codes: {},
+        get: function(A, k) {
+            const v = (A && A[k] !== undefined) ? A[k] : null;
+            return (this.codes[k] || []).some(c => eq(c, v)) ? null : v;
+        },
+        truthy: truthy,
+        answered: answered,
+        isin: (a, b) => {
+            if (!Array.isArray(b) || a === null || a === undefined) return false;
+            return Array.isArray(a) ? a.some(x => b.some(y => eq(x, y))) : b.some(y => eq(a, y));
+        },
+        count: (...a) => a.reduce((n, v) => n + (Array.isArray(v) ? v.length : truthy(v) ? 1 : 0), 0),
+        sum: (...a) => {
+            const xs = [].concat(...a.map(v => Array.isArray(v) ? v : [v])).map(num).filter(x => x !== null);
+            return xs.length ? res(xs.reduce((s, x) => s + x, 0)) : null;
+        },
+        neg: a => { const x = num(a); return x === null ? null : res(-x); },
+        add: arith((x, y) => res(x + y)),
+        sub: arith((x, y) => res(x - y)),
+        mul: arith((x, y) => res(x * y)),
+        div: arith((x, y) => y === 0 ? null : res(x / y)),
+        rem: arith((x, y) => y === 0 ? null : res(x % y)),
+        pow: arith((x, y) => res(Math.pow(x, y))),
+        eq: eq,
+        ne: (a, b) => !eq(a, b),
+        lt: cmp(o => o < 0), le: cmp(o => o <= 0), gt: cmp(o => o > 0), ge: cmp(o => o >= 0),
+        round: (a, d) => {
+            const x = num(a); if (x === null) return null;
+            const s = Math.pow(10, num(d) || 0);
+            return res(Math.sign(x) * Math.floor(Math.abs(x) * s + 0.5) / s);
+        },
+        floor: a => { const x = num(a); return x === null ? null : Math.floor(x); },
+        ceil: a => { const x = num(a); return x === null ? null : Math.ceil(x); },
+        abs: a => { const x = num(a); return x === null ? null : Math.abs(x); },
+        min: (...a) => { const xs = a.map(num); return xs.includes(null) ? null : Math.min(...xs); },
+        max: (...a) => { const xs = a.map(num); return xs.includes(null) ? null : Math.max(...xs); },
+        coalesce: (...a) => { const v = a.find(v => v !== null && v !== undefined); return v === undefined ? null : v; },
+        years_since: a => {
+            const m = typeof a === "string" && /^(\d{4})-(\d{2})-(\d{2})/.exec(a.trim());
+            if (!m) return null;
+            const now = new Date();
+            let years = now.getFullYear() - Number(m[1]);
+            if (now.getMonth() + 1 < Number(m[2]) || (now.getMonth() + 1 === Number(m[2]) && now.getDate() < Number(m[3]))) years -= 1;
+            return years;
+        },
+    };
+})();
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(src: &str, answers: Value) -> Value {
+        parse(src).unwrap().eval(answers.as_object().unwrap())
+    }
+
+    #[test]
+    fn parses_and_evaluates() {
+        let a = json!({ "weight": 72, "height": "180", "consent": "yes", "none": null });
+        assert_eq!(eval("round(weight / (height / 100) ^ 2, 1)", a.clone()), json!(22.2));
+        assert_eq!(eval("-2^2 + 10 % 4", a.clone()), json!(-2));
+        assert_eq!(eval("weight > 70 and not (consent == \"no\")", a.clone()), json!(true));
+        assert_eq!(eval("weight + none", a.clone()), Value::Null);
+        assert_eq!(eval("if(none, 1, coalesce(none, 'x'))", a.clone()), json!("x"));
+        assert_eq!(eval("weight / 0", a), Value::Null);
+
+        let e = parse("a + b * a").unwrap();
+        assert_eq!(e.references(), vec!["a", "b"]);
+        assert_eq!(e.to_js(), "X.add(X.get(A,\"a\"),X.mul(X.get(A,\"b\"),X.get(A,\"a\")))");
+
+        assert!(parse("1 +").is_err());
+        assert!(parse("nope(1)").unwrap_err().contains("unknown function"));
+        assert!(parse("round()").is_err());
+        assert!(parse("(1").is_err());
+    }
+
+    #[test]
+    fn lists_counts_and_sums() {
+        let a = json!({ "q5": [1, 3], "smoker": 2, "x": null, "headache": 1, "fever": 0 });
+        assert_eq!(eval("smoker in [1, 2] and 3 in q5", a.clone()), json!(true));
+        assert_eq!(eval("q5 in [2, 4] or x in [null]", a.clone()), json!(false));
+        assert_eq!(eval("count(q5) + count(headache == 1, fever == 1)", a.clone()), json!(3));
+        assert_eq!(eval("sum(q5, x, smoker)", a.clone()), json!(6));
+        assert_eq!(eval("sum(x)", a.clone()), Value::Null);
+        assert_eq!(eval("answered(x) or not answered(q5)", a), json!(false));
+    }
+
+    #[test]
+    fn type_checks() {
+        let types = |name: &str| match name {
+            "age" => Some(Type::Number),
+            "sex" => Some(Type::String),
+            "q5" => Some(Type::List),
+            _ => None,
+        };
+        let check = |src: &str| parse(src).unwrap().check(&types);
+        assert_eq!(check("age >= 18 and sex == 'f'"), Ok(Type::Bool));
+        assert_eq!(check("if(age > 1, 'a', null)"), Ok(Type::String));
+        assert_eq!(check("sum(q5, age) / count(q5)"), Ok(Type::Number));
+        assert_eq!(check("html_input + 1"), Ok(Type::Number));
+        assert!(check("sex + 1").unwrap_err().contains("`+` needs a number, not a string"));
+        assert!(check("age == 'x'").unwrap_err().contains("compares a number with a string"));
+        assert!(check("age in 3").unwrap_err().contains("`in` needs a list"));
+        assert!(check("[1, 'a']").unwrap_err().contains("mixes"));
+        assert!(check("q5 < 2").is_err());
+        assert!(check("years_since(age)").is_err());
+    }
+
+    /// The compiled JS and the Rust evaluator must agree. Runs the runtime helpers in
+    /// node, which must be installed.
+    #[test]
+    fn rust_and_js_evaluators_agree() {
+        let answers = json!({
+            "weight": 72, "height": "180", "consent": "yes", "none": null, "q5": [1, 3],
+            "smoker": 2, "name": "Ann", "empty": "", "dob": "2000-02-29", "neg": -7.5, "dk": -99,
+        });
+        // `dk` holds its question's "don't know" code, which reads as unanswered
+        let codes: HashMap<String, Vec<OptionValue>> = [("dk".to_string(), vec![OptionValue::parse("-99")])].into();
+        let cases = [
+            "round(weight / (height / 100) ^ 2, 1)",
+            "-2^2 + 10 % 4 - neg % 2",
+            "weight > 70 and not (consent == 'no')",
+            "weight + none",
+            "if(none, 1, coalesce(none, empty, 'x'))",
+            "weight / 0",
+            "smoker in [1, 2] and 3 in q5",
+            "q5 in [2, 4] or none in [null]",
+            "count(q5) + count(smoker == 2, none, empty, consent)",
+            "sum(q5, none, smoker, height)",
+            "sum(none)",
+            "answered(empty) or answered(none) or not answered(q5)",
+            "name < 'Bob' and name >= 'A'",
+            "none < 1",
+            "min(weight, height, 3) + max(neg, -10)",
+            "round(-2.5) + round(2.45, 1) + floor(neg) + ceil(neg) + abs(neg)",
+            "'1' == 1 and none == null and none != 0",
+            "q5 == [1, 3]",
+            "years_since(dob) > 20 and years_since(name) == null",
+            "height * 1.5 - 0.1",
+            "coalesce(dk, 5) + count(dk, smoker)",
+            "answered(dk) or dk in [-99]",
+        ];
+        let mut script = format!(
+            "const window = globalThis;\n{}\nconst X = window.SQE.expr;\nX.codes = {{ dk: [-99] }};\nconst A = {};\nconsole.log(JSON.stringify([",
+            RUNTIME_JS, answers
+        );
+        for src in cases {
+            script.push_str(&format!("((A, X) => {})(A, X),", parse(src).unwrap().to_js()));
+        }
+        script.push_str("]));\n");
+        let output = std::process::Command::new("node")
+            .arg("-e")
+            .arg(&script)
+            .output()
+            .expect("node is needed to run the JS side of this test");
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let js: Vec<Value> = serde_json::from_slice(&output.stdout).unwrap();
+        for (src, js_value) in cases.iter().zip(js) {
+            let rust_value = parse(src).unwrap().eval_with_codes(answers.as_object().unwrap(), &codes);
+            let same = match (rust_value.as_f64(), js_value.as_f64()) {
+                (Some(a), Some(b)) => a == b,
+                _ => rust_value == js_value,
+            };
+            assert!(same, "`{}`: Rust {} but JS {}", src, rust_value, js_value);
+        }
+    }
+}
@@ -0,0 +1,160 @@
+// === src/formatter.rs ===
+
+use std::io;
+
+use crate::transcompiler::read_brace_block;
+
+/// Format .sqe source text into the canonical layout:
+///
+/// * top-level directives are trimmed and `title` values are quoted,
+/// * runs of blank lines collapse to one, and every `@p` is preceded by a blank line,
+/// * `choice` bodies are unindented with `label >> value` spacing and indented `.addons`,
+/// * `insert` bodies are indented by four spaces,
+/// * code blocks (`html`, `js`, `css` and unknown blocks) are kept verbatim apart from
+///   trailing whitespace and blank lines around the body, since their whitespace can
+///   matter (template literals, `<pre>`, multi-line strings).
+///
+/// Comments are preserved. Formatting is idempotent.
+pub fn format_source(src: &str) -> io::Result<String> {
+    let mut lines = src.lines().map(|l| Ok(l.to_string())).peekable();
+    let mut out: Vec<String> = Vec::new();
+    let mut pending_blank = false;
+
+    while let Some(line_res) = lines.next() {
+        let raw: String = line_res?;
+        let line = raw.trim();
+
+        if line.is_empty() {
+            pending_blank = true;
+            continue;
+        }
+
+        let is_page = line.starts_with("@p");
+        if !out.is_empty() && (pending_blank || is_page) {
+            out.push(String::new());
+        }
+        pending_blank = false;
+
+        if line.starts_with("//") {
+            out.push(line.to_string());
+        } else if let Some(rest) = line.strip_prefix("title") {
+            out.push(format!("title {}", quote_value(rest.trim())));
+        } else if let Some(rest) = line.strip_prefix("@p") {
+            out.push(format!("@p {}", rest.trim()).trim_end().to_string());
+        } else if line.starts_with("import") {
+            out.push(line.to_string());
+        } else if let Some(open_pos) = raw.find('{') {
+            let header = raw[..open_pos].split_whitespace().collect::<Vec<_>>().join(" ");
+            let body = read_brace_block(&mut lines, &raw[open_pos + 1..])?;
+            out.push(format!("{} {{", header));
+            let keyword = header.split_whitespace().next().unwrap_or("");
+            if keyword.starts_with("choice") {
+                format_choice_body(&body, &mut out);
+            } else if keyword.starts_with("insert") {
+                format_text_body(&body, "    ", &mut out);
+            } else {
+                format_code_body(&body, &mut out);
+            }
+            out.push("}".to_string());
+        } else {
+            out.push(line.to_string());
+        }
+    }
+
+    let mut formatted = out.join("\n");
+    formatted.push('\n');
+    Ok(formatted)
+}
+
+/// Quote a directive value the way the compiler reads it back.
+fn quote_value(rest: &str) -> String {
+    if rest.starts_with('"') {
+        format!("\"{}\"", rest.split('"').nth(1).unwrap_or(""))
+    } else if rest.is_empty() || rest.contains('"') {
+        rest.to_string()
+    } else {
+        format!("\"{}\"", rest)
+    }
+}
+
+fn format_choice_body(body: &str, out: &mut Vec<String>) {
+    let mut in_addons = false;
+    let mut seen_question = false;
+    for ln in body.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        if in_addons {
+            if ln == "]" {
+                in_addons = false;
+                out.push(ln.to_string());
+            } else {
+                out.push(format!("  {}", ln));
+            }
+            continue;
+        }
+        if !seen_question {
+            // the first line is the question text and is kept as written
+            seen_question = true;
+            out.push(ln.to_string());
+        } else if ln.starts_with(".addons") {
+            in_addons = true;
+            out.push(".addons [".to_string());
+        } else if let Some((label, value)) = ln.split_once(">>") {
+            out.push(format!("{} >> {}", label.trim(), value.trim()));
+        } else {
+            out.push(ln.to_string());
+        }
+    }
+}
+
+/// Trim every line and indent it; keeps single blank lines between paragraphs.
+fn format_text_body(body: &str, indent: &str, out: &mut Vec<String>) {
+    let lines: Vec<&str> = body.lines().map(str::trim).collect();
+    let mut blank = false;
+    for ln in trim_blank_edges(&lines) {
+        if ln.is_empty() {
+            blank = true;
+            continue;
+        }
+        if blank {
+            out.push(String::new());
+            blank = false;
+        }
+        out.push(format!("{}{}", indent, ln));
+    }
+}
+
+/// Keep a code block as written, only dropping trailing whitespace and the blank
+/// lines before and after its contents.
+fn format_code_body(body: &str, out: &mut Vec<String>) {
+    let lines: Vec<&str> = body.lines().map(str::trim_end).collect();
+    out.extend(trim_blank_edges(&lines).iter().map(|l| l.to_string()));
+}
+
+fn trim_blank_edges<'a, 'b>(lines: &'a [&'b str]) -> &'a [&'b str] {
+    let start = lines.iter().position(|l| !l.trim().is_empty()).unwrap_or(lines.len());
+    let end = lines.iter().rposition(|l| !l.trim().is_empty()).map_or(start, |e| e + 1);
+    &lines[start..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_and_is_idempotent() {
+        let src = "title Demo\n\n\n@p   Page 1\nchoice q1 {\n   Do you?\nYes>>1\n  No   >>  0\n.addons [\n.script[console.log(1)]\n]\n}\n// note\n  insert {\nHello\n\n\n  world\n}\njs {\n      if (x) {\n          y();\n      }\n}\n@p Two\n";
+        let once = format_source(src).unwrap();
+        assert_eq!(
+            once,
+            "title \"Demo\"\n\n@p Page 1\nchoice q1 {\nDo you?\nYes >> 1\nNo >> 0\n.addons [\n  .script[console.log(1)]\n]\n}\n// note\ninsert {\n    Hello\n\n    world\n}\njs {\n      if (x) {\n          y();\n      }\n}\n\n@p Two\n"
+        );
+        assert_eq!(format_source(&once).unwrap(), once);
+    }
+
+    #[test]
+    fn keeps_code_blocks_verbatim() {
+        let body = "    const msg = `line one\n  indented line\nlast`;\n\tif (x) {\n\t\ty(\"  two  spaces\");\n\t}";
+        let html = "<pre>\n  a\n    b\n</pre>";
+        let src = format!("@p P\njs {{\n{}\n}}\nhtml {{\n{}   \n}}\n", body, html);
+        assert_eq!(format_source(&src).unwrap(), format!("@p P\njs {{\n{}\n}}\nhtml {{\n{}\n}}\n", body, html));
+    }
+}
@@ -0,0 +1,161 @@
+// === src/grading.rs ===
+
+//! Quiz grading (`mode quiz`). A choice earns the points of the options chosen: an
+//! option's `.points`, else 1 when it is `.correct`. Multiple choice sums the chosen
+//! options (never below 0) and is correct when exactly the correct options are
+//! chosen. Questions a `draw` did not pick for the respondent do not count. The page
+//! runtime grades the same way (`SQE.grade`).
+
+use serde_json::{Value, json};
+
+use crate::export::{choice_items, draws};
+use crate::items::{Choose, Quiz};
+use crate::responses::Answers;
+use crate::scoring::number;
+use crate::transcompiler::Entry;
+
+/// Grade of one question.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemGrade {
+    pub key: String,
+    pub points: f64,
+    pub max: f64,
+    pub correct: bool,
+}
+
+/// Grade of one response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuizGrade {
+    pub points: f64,
+    pub max: f64,
+    pub items: Vec<ItemGrade>,
+}
+
+impl QuizGrade {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "points": number(self.points),
+            "max": number(self.max),
+            "items": self.items.iter().map(|i| (i.key.clone(), json!({
+                "points": number(i.points),
+                "max": number(i.max),
+                "correct": i.correct,
+            }))).collect::<serde_json::Map<_, _>>(),
+        })
+    }
+}
+
+/// The document's `mode quiz`, if any.
+pub fn quiz(ast: &[Entry]) -> Option<Quiz> {
+    ast.iter().find_map(|e| match e {
+        Entry::Quiz(q) => Some(*q),
+        _ => None,
+    })
+}
+
+/// Choices that count in the quiz, as `(answer key, question)`.
+pub fn graded_items(ast: &[Entry]) -> Vec<(String, &Choose)> {
+    choice_items(ast).into_iter().filter(|(_, _, c)| c.is_graded()).map(|(_, k, c)| (k, c)).collect()
+}
+
+/// Most points a question can earn.
+pub fn max_points(c: &Choose) -> f64 {
+    let points = (0..c.options.len()).map(|i| c.option_points(i));
+    if c.is_multiple() { points.filter(|p| *p > 0.0).sum() } else { points.fold(0.0, f64::max) }
+}
+
+pub fn grade_item(key: &str, c: &Choose, answer: Option<&Value>) -> ItemGrade {
+    let chosen: Vec<usize> = match answer {
+        Some(Value::Array(values)) => (0..c.options.len()).filter(|&i| values.iter().any(|v| c.options[i].1.matches(v))).collect(),
+        Some(v) => c.options.iter().position(|(_, o)| o.matches(v)).into_iter().collect(),
+        None => Vec::new(),
+    };
+    let points: f64 = chosen.iter().map(|&i| c.option_points(i)).sum();
+    let correct = if c.is_multiple() {
+        !chosen.is_empty() && (0..c.options.len()).all(|i| chosen.contains(&i) == c.marks[i].correct)
+    } else {
+        chosen.first().is_some_and(|&i| c.marks[i].correct)
+    };
+    ItemGrade {
+        key: key.to_string(),
+        points: if c.is_multiple() { points.max(0.0) } else { points },
+        max: max_points(c),
+        correct,
+    }
+}
+
+/// Grade every graded question of a response.
+pub fn grade(ast: &[Entry], answers: &Answers) -> QuizGrade {
+    let draws = draws(ast);
+    let drawn = |key: &str| draws.iter().all(|(_, d)| !d.item_keys().contains(&key) || d.picked(answers, key));
+    let items: Vec<ItemGrade> = graded_items(ast)
+        .into_iter()
+        .filter(|(k, _)| drawn(k))
+        .map(|(k, c)| grade_item(&k, c, answers.get(&k)))
+        .collect();
+    QuizGrade {
+        points: items.iter().map(|i| i.points).sum(),
+        max: items.iter().map(|i| i.max).sum(),
+        items,
+    }
+}
+
+/// Grading data for the page runtime: `{key: {multiple, max, options: [{value, label,
+/// correct, points, feedback}]}}`.
+pub fn runtime_json(ast: &[Entry]) -> Value {
+    let items: serde_json::Map<String, Value> = graded_items(ast)
+        .into_iter()
+        .map(|(key, c)| {
+            let options: Vec<Value> = c
+                .options
+                .iter()
+                .zip(&c.marks)
+                .enumerate()
+                .map(|(i, ((label, value), marks))| {
+                    json!({
+                        "value": value.to_json(),
+                        "label": label,
+                        "correct": marks.correct,
+                        "points": number(c.option_points(i)),
+                        "feedback": marks.feedback,
+                    })
+                })
+                .collect();
+            (key, json!({ "multiple": c.is_multiple(), "max": number(max_points(c)), "options": options }))
+        })
+        .collect();
+    Value::Object(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcompiler::Question;
+
+    #[test]
+    fn grades_single_and_multiple_choice() {
+        let choice = |src: &str, id: &str| Question::Choose(Box::new(Choose::parse(src, Some(id.into()))));
+        let ast = vec![
+            Entry::Quiz(Quiz::default()),
+            Entry::Page {
+                title: "Q".to_string(),
+                time_limit: None,
+                content: vec![
+                    choice("Capital?\nParis >> 1 .correct .points 2\nLyon >> 2\nNice >> 3 .points 0.5", "cap"),
+                    choice("Primes?\nTwo >> 2 .correct\nThree >> 3 .correct\nFour >> 4 .points -1\n.addons [\nmultiple\n]", "pr"),
+                    choice("Ungraded?\nYes >> 1\nNo >> 0", "u"),
+                ],
+            },
+        ];
+        let answers = json!({ "cap": 3, "pr": [2, 3], "u": 1 });
+        let g = grade(&ast, answers.as_object().unwrap());
+        assert_eq!(g.items.len(), 2);
+        assert_eq!((g.items[0].points, g.items[0].max, g.items[0].correct), (0.5, 2.0, false));
+        assert_eq!((g.items[1].points, g.items[1].max, g.items[1].correct), (2.0, 2.0, true));
+        assert_eq!((g.points, g.max), (2.5, 4.0));
+
+        let wrong = json!({ "cap": 1, "pr": [4] });
+        let g = grade(&ast, wrong.as_object().unwrap());
+        assert_eq!((g.items[0].points, g.items[1].points, g.items[1].correct), (2.0, 0.0, false));
+    }
+}
@@ -0,0 +1,200 @@
+// === src/items/bank.rs ===
+
+use serde_json::Value;
+
+use crate::expr::{self, Expr};
+use crate::items::Choose;
+use crate::items::common::is_key;
+use crate::responses::Answers;
+use crate::transcompiler::read_brace_block;
+
+/// A pool of tagged questions that pages `draw` from. Banks usually live in their own
+/// file, brought in with `import "bank.sqe"`; every question needs an id.
+///
+/// ```text
+/// bank exam {
+///     choice q001 {
+///         Which sign means "wear ear protection"?
+///         ...
+///         .tag topic safety
+///         .tag level 2
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Bank {
+    pub name: String,
+    pub items: Vec<Choose>,
+}
+
+impl Bank {
+    /// Parse the name after `bank` and the block of `choice` questions.
+    pub fn parse(name: &str, block: &str) -> Result<Self, String> {
+        let name = name.trim();
+        if !is_key(name) {
+            return Err(format!("invalid bank name `{}`", name));
+        }
+        let mut items: Vec<Choose> = Vec::new();
+        let mut lines = block.lines().map(|l| Ok::<_, std::io::Error>(l.to_string())).peekable();
+        while let Some(Ok(raw)) = lines.next() {
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+            let Some(rest) = line.strip_prefix("choice") else {
+                return Err(format!("bank {}: expected `choice <id> {{`, found `{}`", name, line));
+            };
+            let (head, after) = rest.split_once('{').unwrap_or((rest, ""));
+            let inner = read_brace_block(&mut lines, after).map_err(|e| format!("bank {}: {}", name, e))?;
+            let Some(id) = head.split_whitespace().next() else {
+                return Err(format!("bank {}: every question needs an id (`choice q001 {{`)", name));
+            };
+            if items.iter().any(|c| c.id.as_deref() == Some(id)) {
+                return Err(format!("bank {}: id `{}` is used twice", name, id));
+            }
+            let choose = Choose::parse(&inner, Some(id.to_string()));
+            choose.check().map_err(|e| format!("bank {}: choice {}: {}", name, id, e))?;
+            items.push(choose);
+        }
+        Ok(Bank {
+            name: name.to_string(),
+            items,
+        })
+    }
+}
+
+/// `draw N from BANK [where <condition>] [as KEY]` on a page: each respondent gets N
+/// questions picked at random from the bank questions whose tags match the condition.
+/// The pick is seeded by the session seed and saved as the list of picked answer keys
+/// under KEY (default `BANK_draw`); questions not picked are stored like hidden ones.
+///
+/// The pick happens in the browser, so the page holds every question of the pool and
+/// only hides the ones not picked: respondents can find the whole pool in the page
+/// source, and in `mode quiz` its correct options and feedback too unless the page is
+/// built with `--hide-answers` (lint rule `quiz-draw`).
+///
+/// ```text
+/// import "exam-bank.sqe"
+///
+/// @p Safety
+/// draw 10 from exam where topic == "safety" and level >= 2
+/// ```
+#[derive(Debug, Clone)]
+pub struct Draw {
+    pub key: String,
+    pub count: usize,
+    pub bank: String,
+    /// `where` condition as written
+    pub filter: Option<String>,
+    /// The matching bank questions, in bank order; filled in by [`Draw::resolve`]
+    pub items: Vec<Choose>,
+}
+
+impl Draw {
+    /// Parse the text after `draw`.
+    pub fn parse(rest: &str) -> Result<Self, String> {
+        let usage = "use `draw <count> from <bank> [where <condition>] [as <key>]`";
+        let mut rest = rest.trim();
+        let mut key = None;
+        if let Some((head, name)) = rest.rsplit_once(" as ")
+            && is_key(name.trim())
+        {
+            key = Some(name.trim().to_string());
+            rest = head.trim_end();
+        }
+        let (count, rest) = rest.split_once(char::is_whitespace).ok_or_else(|| format!("draw: {}", usage))?;
+        let count: usize = count.parse().map_err(|_| format!("draw: `{}` is not a number of questions", count))?;
+        if count == 0 {
+            return Err("draw: the count must be at least 1".to_string());
+        }
+        let rest = rest.trim_start().strip_prefix("from ").ok_or_else(|| format!("draw: {}", usage))?.trim_start();
+        let (bank, filter) = match rest.split_once(char::is_whitespace) {
+            Some((bank, filter)) => {
+                let filter = filter.trim_start().strip_prefix("where ").ok_or_else(|| format!("draw: {}", usage))?;
+                (bank, Some(filter.trim().to_string()))
+            }
+            None => (rest, None),
+        };
+        if !is_key(bank) {
+            return Err(format!("draw: invalid bank name `{}`", bank));
+        }
+        Ok(Draw {
+            key: key.unwrap_or_else(|| format!("{}_draw", bank)),
+            count,
+            bank: bank.to_string(),
+            filter,
+            items: Vec::new(),
+        })
+    }
+
+    /// Fill in the bank questions whose tags satisfy the `where` condition. The
+    /// condition may only name tags the bank uses, and enough questions must match.
+    pub fn resolve(&mut self, bank: &Bank) -> Result<(), String> {
+        let filter: Option<Expr> = self.filter.as_deref().map(expr::parse).transpose().map_err(|e| format!("where: {}", e))?;
+        if let Some(filter) = &filter
+            && let Some(name) = filter
+                .references()
+                .into_iter()
+                .find(|r| !bank.items.iter().any(|c| c.tags.iter().any(|(t, _)| t == r)))
+        {
+            return Err(format!("where: no question in bank `{}` has the tag `{}`", bank.name, name));
+        }
+        self.items = bank
+            .items
+            .iter()
+            .filter(|c| {
+                let tags: Answers = c.tags.iter().map(|(t, v)| (t.clone(), v.to_json())).collect();
+                filter.as_ref().is_none_or(|f| expr::truthy(&f.eval(&tags)))
+            })
+            .cloned()
+            .collect();
+        if self.items.len() < self.count {
+            return Err(format!(
+                "only {} question(s) of bank `{}` match, {} are drawn",
+                self.items.len(),
+                bank.name,
+                self.count
+            ));
+        }
+        Ok(())
+    }
+
+    /// Answer keys of the questions in the pool.
+    pub fn item_keys(&self) -> Vec<&str> {
+        self.items.iter().filter_map(|c| c.id.as_deref()).collect()
+    }
+
+    /// Whether the response drew the question `key` (its answer key is in the saved pick).
+    pub fn picked(&self, answers: &Answers, key: &str) -> bool {
+        matches!(answers.get(&self.key), Some(Value::Array(keys)) if keys.iter().any(|k| k.as_str() == Some(key)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draws_from_tagged_pool() {
+        let bank = Bank::parse(
+            "exam",
+            "choice a {\nA?\nYes\n.tag topic safety\n.tag level 1\n}\nchoice b {\nB?\nYes\n.tag topic safety\n.tag level 3\n}\nchoice c {\nC?\nYes\n.tag topic tools\n}",
+        )
+        .unwrap();
+        assert_eq!(bank.items.len(), 3);
+
+        let mut draw = Draw::parse(r#"1 from exam where topic == "safety" and level >= 2 as picked"#).unwrap();
+        assert_eq!((draw.key.as_str(), draw.count, draw.bank.as_str()), ("picked", 1, "exam"));
+        draw.resolve(&bank).unwrap();
+        assert_eq!(draw.item_keys(), vec!["b"]);
+
+        let mut all = Draw::parse("2 from exam").unwrap();
+        assert_eq!(all.key, "exam_draw");
+        all.resolve(&bank).unwrap();
+        assert_eq!(all.item_keys(), vec!["a", "b", "c"]);
+
+        assert!(Draw::parse("3 from exam where topic == \"safety\"").unwrap().resolve(&bank).unwrap_err().contains("only 2"));
+        assert!(Draw::parse("1 from exam where topc == 1").unwrap().resolve(&bank).unwrap_err().contains("`topc`"));
+        assert!(Bank::parse("exam", "choice {\nQ?\nYes\n}").is_err());
+    }
+}
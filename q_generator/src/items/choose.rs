@@ -1,32 +1,108 @@
 // === src/items/choose.rs ===
 
-use crate::items::common::{escape_attr, escape_html, js_literal_for_key};
+use serde_json::Value;
+
+use crate::expr::{self, Expr};
+use crate::items::common::{escape_attr, escape_html, is_key, js_literal_for_key, render_pipes};
+use crate::items::missing::{self, MissingCode, MissingKind};
+use crate::items::value::{OptionValue, ValueType, check_values};
+
+/// Placeholder question text used when a choice block has no lines at all.
+pub const NO_QUESTION: &str = "⚠ no question";
+
+/// Quiz marks of one option: `.correct`, `.points N` and `.feedback <text>` after the
+/// option (see [`Choose::option_points`]).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OptionMarks {
+    pub correct: bool,
+    pub points: Option<f64>,
+    /// Shown under the option once it is graded
+    pub feedback: Option<String>,
+}
 
 /// A Choose node: covers both multiple-choice and boolean-style questions.
 #[derive(Debug, Clone)]
 pub struct Choose {
     pub id: Option<String>,
     pub question: String,
-    /// options = vec![(label, value_string), ...]
-    pub options: Vec<(String, String)>,
+    /// options = vec![(label, value), ...]
+    pub options: Vec<(String, OptionValue)>,
+    /// Quiz marks, one per option
+    pub marks: Vec<OptionMarks>,
+    /// "Other, please specify" options (`Other >> 99 .other [key]`): option index and
+    /// the key given for its text field, if any (see [`Choose::other_keys`])
+    pub other: Vec<(usize, Option<String>)>,
+    /// Value type declared with `.type integer|number|boolean|string`
+    pub value_type: Option<ValueType>,
+    /// A `.type` line naming no known type, reported by [`Choose::check_values`]
+    unknown_type: Option<String>,
+    /// Missing-value codes from `.missing` lines; after compiling, the resolved codes
+    /// (see [`missing::resolve`])
+    pub missing: Vec<MissingCode>,
+    /// `.missing` lines and option modifiers that did not parse, reported by
+    /// [`Choose::check`]
+    errors: Vec<String>,
     pub addons: Vec<String>,
     pub script_lines: Vec<String>,
+    /// Condition from a `.show if <expression>` line; the question is hidden (and its
+    /// answer cleared) while it is false.
+    pub show_if: Option<String>,
+    /// `.tag <name> <value>` lines, matched by the `where` of a `draw` from a bank
+    pub tags: Vec<(String, OptionValue)>,
 }
 
 impl Choose {
     pub fn parse(block: &str, id: Option<String>) -> Self {
         let mut lines = block.lines().map(|l| l.trim()).filter(|l| !l.is_empty());
-        let question = lines.next().unwrap_or("⚠ no question").to_string();
+        let question = lines.next().unwrap_or(NO_QUESTION).to_string();
 
+        // (label, value source text); typed once the whole block (and `.type`) is read
         let mut options: Vec<(String, String)> = Vec::new();
+        let mut other: Vec<(usize, Option<String>)> = Vec::new();
+        let mut value_type: Option<ValueType> = None;
+        let mut unknown_type: Option<String> = None;
+        let mut missing: Vec<MissingCode> = Vec::new();
+        let mut errors: Vec<String> = Vec::new();
+        let mut marks: Vec<OptionMarks> = Vec::new();
         let mut addons: Vec<String> = Vec::new();
         let mut script_lines: Vec<String> = Vec::new();
         let mut auto_idx: usize = 0;
+        let mut show_if: Option<String> = None;
+        let mut tags: Vec<(String, OptionValue)> = Vec::new();
 
         let lines_vec: Vec<String> = lines.map(String::from).collect();
         let mut i = 0usize;
         while i < lines_vec.len() {
             let ln = lines_vec[i].trim();
+            if let Some(name) = ln.strip_prefix(".type ") {
+                value_type = ValueType::parse(name);
+                if value_type.is_none() {
+                    unknown_type = Some(name.trim().to_string());
+                }
+                i += 1;
+                continue;
+            }
+            if let Some(rest) = ln.strip_prefix(".missing ") {
+                match MissingCode::parse(rest) {
+                    Ok(code) => missing.push(code),
+                    Err(e) => errors.push(e),
+                }
+                i += 1;
+                continue;
+            }
+            if let Some(cond) = ln.strip_prefix(".show if ") {
+                show_if = Some(cond.trim().to_string());
+                i += 1;
+                continue;
+            }
+            if let Some(rest) = ln.strip_prefix(".tag ") {
+                match rest.trim().split_once(char::is_whitespace) {
+                    Some((name, value)) if is_key(name) => tags.push((name.to_string(), OptionValue::parse(value))),
+                    _ => errors.push(format!("`.tag {}` needs a name and a value, e.g. `.tag topic safety`", rest.trim())),
+                }
+                i += 1;
+                continue;
+            }
             if ln.starts_with(".addons") {
                 i += 1;
                 while i < lines_vec.len() {
@@ -37,12 +113,12 @@ impl Choose {
                     if inner.starts_with(".script") {
                         // handle inline or multi-line .script[ ... ]
                         if inner.contains('[') && inner.contains(']') {
-                            if let Some(start) = inner.find('[') {
-                                if let Some(end) = inner.rfind(']') {
-                                    let s = inner[start + 1..end].trim();
-                                    if !s.is_empty() {
-                                        script_lines.push(s.to_string());
-                                    }
+                            if let Some(start) = inner.find('[')
+                                && let Some(end) = inner.rfind(']')
+                            {
+                                let s = inner[start + 1..end].trim();
+                                if !s.is_empty() {
+                                    script_lines.push(s.to_string());
                                 }
                             }
                         } else if inner.contains('[') {
@@ -78,9 +154,22 @@ impl Choose {
                 continue;
             }
 
+            let (ln, modifiers) = split_modifiers(ln);
+            match modifiers {
+                Ok((option_marks, other_key)) => {
+                    if let Some(key) = other_key {
+                        other.push((options.len(), key));
+                    }
+                    marks.push(option_marks);
+                }
+                Err(e) => {
+                    errors.push(e);
+                    marks.push(OptionMarks::default());
+                }
+            }
             if ln.contains(">>") {
                 let parts: Vec<&str> = ln.splitn(2, ">>").collect();
-                let label = parts.get(0).map(|s| s.trim()).unwrap_or("").to_string();
+                let label = parts.first().map(|s| s.trim()).unwrap_or("").to_string();
                 let val = parts.get(1).map(|s| s.trim()).unwrap_or("").to_string();
                 options.push((label, val));
             } else {
@@ -91,12 +180,124 @@ impl Choose {
             i += 1;
         }
 
+        let options = options
+            .into_iter()
+            .map(|(label, text)| {
+                let value = match value_type {
+                    Some(t) => OptionValue::parse_as(&text, t),
+                    None => OptionValue::parse(&text),
+                };
+                (label, value)
+            })
+            .collect();
         Choose {
             id,
             question,
             options,
+            marks,
+            other,
+            value_type,
+            unknown_type,
+            missing,
+            errors,
             addons,
             script_lines,
+            show_if,
+            tags,
+        }
+    }
+
+    /// Problems found while parsing: bad `.missing` lines or option modifiers, unknown
+    /// or mixed value types.
+    pub fn check(&self) -> Result<(), String> {
+        if let Some(e) = self.errors.first() {
+            return Err(e.clone());
+        }
+        self.check_values().map(|_| ())
+    }
+
+    /// Points for choosing option `i`: its `.points`, else 1 when it is `.correct`.
+    pub fn option_points(&self, i: usize) -> f64 {
+        let marks = &self.marks[i];
+        marks.points.unwrap_or(if marks.correct { 1.0 } else { 0.0 })
+    }
+
+    /// Whether the question counts in a quiz: some option is correct or has points.
+    pub fn is_graded(&self) -> bool {
+        self.marks.iter().any(|m| m.correct || m.points.is_some())
+    }
+
+    /// Type of the option values: the declared `.type`, else inferred. Values that do not
+    /// fit the declared type, or of different types, are an error.
+    pub fn check_values(&self) -> Result<ValueType, String> {
+        if let Some(name) = &self.unknown_type {
+            return Err(format!("unknown value type `{}` (use integer, number, boolean or string)", name));
+        }
+        check_values(self.options.iter().map(|(_, v)| v), self.value_type)
+    }
+
+    /// Answer keys of the "other" text fields as `(option index, key)`: the key given
+    /// after `.other`, else `<key>_other`, or `<key>_other_<n>` (n = option number) when
+    /// several options of the question have one.
+    pub fn other_keys(&self, store_key: &str) -> Vec<(usize, String)> {
+        let defaults = self.other.iter().filter(|(_, k)| k.is_none()).count();
+        self.other
+            .iter()
+            .map(|(i, key)| {
+                let key = match key {
+                    Some(k) => k.clone(),
+                    None if defaults == 1 => format!("{}_other", store_key),
+                    None => format!("{}_other_{}", store_key, i + 1),
+                };
+                (*i, key)
+            })
+            .collect()
+    }
+
+    /// Replace the `.missing` declarations by the question's resolved codes, given the
+    /// document-level ones. Codes may not collide with option values.
+    pub fn resolve_missing(&mut self, document: &[&MissingCode]) -> Result<(), String> {
+        let codes = missing::resolve(&self.missing, document)?;
+        for code in &codes {
+            let value = code.value.as_ref().map(|v| v.to_json()).unwrap_or_default();
+            if let Some((label, _)) = self.options.iter().find(|(_, o)| o.matches(&value)) {
+                return Err(format!("missing {} code `{}` is also the value of option \"{}\"", code.kind.name(), value, label));
+            }
+        }
+        self.missing = codes;
+        Ok(())
+    }
+
+    /// The kind of missing-value code an answer is, if it is one.
+    pub fn missing_kind(&self, answer: &Value) -> Option<MissingKind> {
+        self.missing
+            .iter()
+            .find(|c| c.value.as_ref().is_some_and(|v| v.matches(answer)))
+            .map(|c| c.kind)
+    }
+
+    /// The parsed `.show if` condition, if any.
+    pub fn condition(&self) -> Option<Result<Expr, String>> {
+        self.show_if.as_deref().map(expr::parse)
+    }
+
+    /// Whether the question must be answered (`.addons [ required ]`).
+    pub fn is_required(&self) -> bool {
+        self.addons.iter().any(|a| a == "required")
+    }
+
+    /// Whether several options may be selected (`.addons [ multiple ]`); the answer is
+    /// then a list of the selected values.
+    pub fn is_multiple(&self) -> bool {
+        self.addons.iter().any(|a| a == "multiple")
+    }
+
+    /// The storage key for this question (used by runtime to store answers).
+    /// Falls back to a positional `{page}_{idx}` key when the choice has no id.
+    pub fn store_key(&self, page_idx: usize, q_idx: usize) -> String {
+        match &self.id {
+            Some(s) if !s.is_empty() => s.clone(),
+            _ => format!("{}_{}", page_idx, q_idx),
         }
     }
 
@@ -104,35 +305,66 @@ impl Choose {
         let qname = format!("p{}_q{}", page_idx, q_idx);
         let mut html = String::new();
  
-        // Determine the storage key for this question (used by runtime to store answers).
-        let store_key = match &self.id {
-            Some(s) if !s.is_empty() => s.clone(),
-            _ => format!("{}_{}", page_idx, q_idx),
-        };
+        let store_key = self.store_key(page_idx, q_idx);
  
+        let required = self.is_required();
         html.push_str(&format!(
-            "<fieldset class=\"question\" data-q=\"{}\">",
-            escape_html(&qname)
+            "<fieldset class=\"question\" data-q=\"{}\"{}>",
+            escape_html(&qname),
+            if required { " data-sqe-required=\"true\" aria-required=\"true\"" } else { "" }
+        ));
+        html.push_str(&format!(
+            "<legend>{}{}</legend>",
+            render_pipes(&escape_html(&self.question)),
+            if required { " <span class=\"required-mark\" aria-hidden=\"true\">*</span>" } else { "" }
         ));
-        html.push_str(&format!("<legend>{}</legend>", escape_html(&self.question)));
  
+        let input_type = if self.is_multiple() { "checkbox" } else { "radio" };
+        let other_keys = self.other_keys(&store_key);
         for (opt_i, (label, value)) in self.options.iter().enumerate() {
             let input_id = format!("{}_opt{}", qname, opt_i);
+            // the text field is enabled by the runtime while its option is selected
+            let other = match other_keys.iter().find(|(i, _)| *i == opt_i) {
+                Some((_, other_key)) => format!(
+                    " <input type=\"text\" class=\"other-text\" id=\"{id}_text\" data-sqe-key=\"{key}\" data-sqe-other-for=\"{id}\" aria-label=\"{label}\" disabled>",
+                    id = escape_attr(&input_id),
+                    key = escape_attr(other_key),
+                    label = escape_attr(label),
+                ),
+                None => String::new(),
+            };
             html.push_str(&format!(
-                "<div><input type=\"radio\" id=\"{id}\" name=\"{qname}\" data-sqe-key=\"{key}\" data-sqe-value=\"{val_esc}\"> <label for=\"{id}\">{label}</label></div>",
+                "<div><input type=\"{input_type}\" id=\"{id}\" name=\"{qname}\" data-sqe-key=\"{key}\" data-sqe-value=\"{val_esc}\" data-sqe-type=\"{val_type}\"> <label for=\"{id}\">{label}</label>{other}</div>",
                 id = escape_attr(&input_id),
                 qname = escape_attr(&qname),
                 key = escape_attr(&store_key),
-                val_esc = escape_attr(value),
+                val_esc = escape_attr(&value.to_string()),
+                val_type = value.value_type().name(),
                 label = escape_html(label),
             ));
         }
+        // "don't know" and "refused" are offered after the regular options
+        let offered = self.missing.iter().filter(|c| c.kind.is_option());
+        for (opt_i, code) in (self.options.len()..).zip(offered) {
+            let Some(value) = &code.value else { continue };
+            let input_id = format!("{}_opt{}", qname, opt_i);
+            html.push_str(&format!(
+                "<div class=\"missing-option\"><input type=\"{input_type}\" id=\"{id}\" name=\"{qname}\" data-sqe-key=\"{key}\" data-sqe-value=\"{val_esc}\" data-sqe-type=\"{val_type}\" data-sqe-missing=\"{kind}\"> <label for=\"{id}\">{label}</label></div>",
+                id = escape_attr(&input_id),
+                qname = escape_attr(&qname),
+                key = escape_attr(&store_key),
+                val_esc = escape_attr(&value.to_string()),
+                val_type = value.value_type().name(),
+                kind = code.kind.name(),
+                label = escape_html(code.label()),
+            ));
+        }
  
         html.push_str("</fieldset>");
 
         // Build JS with proper brace escaping for format!
         let mut js = format!(
-            "(function() {{\n  try {{\n    if (!window.SQE_ANSWERS) window.SQE_ANSWERS = {{}};\n    const inputs = document.querySelectorAll(\"input[name='{}']\");\n    inputs.forEach(i => {{\n      i.addEventListener('change', function(e) {{\n        try {{\n          const raw = this.dataset.sqeValue;\n          const num = Number(raw);\n          const val = (Number.isFinite(num) && raw !== '') ? num : raw;\n          // Prefer centralized collection when available so checkboxes/radios/text are normalized.\n          if (window.SQE && typeof window.SQE.collectAnswers === 'function') {{\n            window.SQE.collectAnswers();\n          }} else {{\n            window.SQE_ANSWERS[{}] = val;\n          }}\n          // Notify runtime that an answer changed.\n          document.dispatchEvent(new CustomEvent('sqe:answer', {{ detail: {{ id: {}, value: val }} }}));\n        }} catch (e) {{ console.error('SQE choose onchange handler error', e); }}\n      }});\n    }});\n  }} catch(e) {{ console.error('SQE choose init error', e); }}\n}}());",
+            "(function() {{\n  try {{\n    if (!window.SQE_ANSWERS) window.SQE_ANSWERS = {{}};\n    const inputs = document.querySelectorAll(\"input[name='{}']\");\n    inputs.forEach(i => {{\n      i.addEventListener('change', function(e) {{\n        try {{\n          const val = window.SQE && typeof window.SQE.typedValue === 'function' ? window.SQE.typedValue(this) : this.dataset.sqeValue;\n          // a \"don't know\" / \"refused\" box excludes the regular options and vice versa\n          if (this.type === 'checkbox' && this.checked) inputs.forEach(o => {{ if (o !== this && !o.getAttribute('data-sqe-missing') !== !this.getAttribute('data-sqe-missing')) o.checked = false; }});\n          // Prefer centralized collection when available so checkboxes/radios/text are normalized.\n          if (window.SQE && typeof window.SQE.collectAnswers === 'function') {{\n            window.SQE.collectAnswers();\n          }} else {{\n            window.SQE_ANSWERS[{}] = val;\n          }}\n          const other = document.getElementById(this.id + '_text');\n          if (other && this.checked && typeof other.focus === 'function') other.focus();\n          // Notify runtime that an answer changed.\n          document.dispatchEvent(new CustomEvent('sqe:answer', {{ detail: {{ id: {}, value: val }} }}));\n        }} catch (e) {{ console.error('SQE choose onchange handler error', e); }}\n      }});\n    }});\n  }} catch(e) {{ console.error('SQE choose init error', e); }}\n}}());",
             qname,
             js_literal_for_key(&store_key),
             js_literal_for_key(&store_key)
@@ -142,10 +374,86 @@ impl Choose {
             js.push_str("\n// user .script lines for this question\n");
             for line in &self.script_lines {
                 js.push_str(line);
-                js.push_str("\n");
+                js.push('\n');
             }
         }
 
         (html, Some(js))
     }
 }
+
+/// Option modifiers, written after the option: `Paris >> 1 .correct .points 2`.
+const MODIFIERS: [&str; 4] = [".correct", ".points", ".other", ".feedback"];
+
+/// Marks and the `.other` text key (`Some(None)` for the derived key) of an option.
+type Modifiers = (OptionMarks, Option<Option<String>>);
+
+/// Split the trailing modifiers off an option line. They start at the first
+/// whitespace-separated modifier word; `.feedback` takes the rest of the line.
+fn split_modifiers(line: &str) -> (&str, Result<Modifiers, String>) {
+    let is_modifier = |word: &str| MODIFIERS.contains(&word);
+    let start = line.char_indices().find(|&(pos, c)| {
+        c == '.'
+            && (pos == 0 || line[..pos].ends_with(char::is_whitespace))
+            && is_modifier(line[pos..].split_whitespace().next().unwrap_or(""))
+    });
+    match start {
+        Some((start, _)) => (line[..start].trim_end(), parse_modifiers(&line[start..])),
+        None => (line, Ok((OptionMarks::default(), None))),
+    }
+}
+
+fn parse_modifiers(text: &str) -> Result<Modifiers, String> {
+    let is_modifier = |word: &str| MODIFIERS.contains(&word);
+    let mut marks = OptionMarks::default();
+    let mut other = None;
+    let mut rest = text.trim_start();
+    while let Some(word) = rest.split_whitespace().next() {
+        let after = rest[word.len()..].trim_start();
+        let next = after.split_whitespace().next().unwrap_or("");
+        rest = after;
+        match word {
+            ".correct" => marks.correct = true,
+            ".feedback" => {
+                marks.feedback = Some(after.trim().to_string()).filter(|f| !f.is_empty());
+                break;
+            }
+            ".points" => {
+                let points = next.parse::<f64>().ok().filter(|p| p.is_finite());
+                marks.points = Some(points.ok_or_else(|| format!("`.points` needs a number, found `{}`", next))?);
+                rest = after[next.len()..].trim_start();
+            }
+            ".other" if !next.is_empty() && !is_modifier(next) => {
+                if !is_key(next) {
+                    return Err(format!("`.other {}`: not a valid answer key", next));
+                }
+                other = Some(Some(next.to_string()));
+                rest = after[next.len()..].trim_start();
+            }
+            ".other" => other = Some(None),
+            _ => return Err(format!("unknown option modifier `{}`", word)),
+        }
+    }
+    Ok((marks, other))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marks_other_options() {
+        let c = Choose::parse("Pet?\nDog >> 1\nOther >> 99 .other\nNone", Some("pet".into()));
+        assert_eq!(c.options[1], ("Other".to_string(), OptionValue::Integer(99)));
+        assert_eq!(c.other_keys("pet"), vec![(1, "pet_other".to_string())]);
+
+        let c = Choose::parse("Pet?\nOther bird .other\nOther fish .other fish_kind\nOther .other", None);
+        assert_eq!(c.options[0], ("Other bird".to_string(), OptionValue::Integer(0)));
+        assert_eq!(
+            c.other_keys("1_0"),
+            vec![(0, "1_0_other_1".to_string()), (1, "fish_kind".to_string()), (2, "1_0_other_3".to_string())]
+        );
+        // only a separate `.other` word marks an option
+        assert!(Choose::parse("Q\nSee x.other >> 1", None).other.is_empty());
+    }
+}
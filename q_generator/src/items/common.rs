@@ -15,6 +15,50 @@ pub fn escape_attr(s: &str) -> String {
     escape_html(s)
 }
 
+/// Keys referenced by `{{key}}` placeholders in a text.
+pub fn pipe_keys(text: &str) -> Vec<String> {
+    let mut keys = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        rest = &rest[start + 2..];
+        if let Some(end) = rest.find("}}")
+            && is_key(rest[..end].trim())
+        {
+            keys.push(rest[..end].trim().to_string());
+            rest = &rest[end + 2..];
+        }
+    }
+    keys
+}
+
+/// Turn `{{key}}` placeholders in already escaped text into spans the runtime fills
+/// with the current answer (piping). Anything that is not a plain key is left as is.
+pub fn render_pipes(escaped: &str) -> String {
+    let mut out = String::new();
+    let mut rest = escaped;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        match after.find("}}") {
+            Some(end) if is_key(after[..end].trim()) => {
+                out.push_str(&rest[..start]);
+                out.push_str(&format!("<span class=\"sqe-pipe\" data-sqe-pipe=\"{}\"></span>", after[..end].trim()));
+                rest = &after[end + 2..];
+            }
+            _ => {
+                out.push_str(&rest[..start + 2]);
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Answer key or variable name usable in piping: letters, digits and `_`.
+pub(crate) fn is_key(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
 /// Produce a JS string literal suitable for embedding directly into generated JS.
 /// Uses `serde_json::to_string` for correct escaping where possible, and falls back
 /// to a manual escape routine on error (never panics). Also neutralizes `</script>`.
@@ -62,6 +106,12 @@ mod tests {
         assert!(js.contains("\\n"));
     }
 
+    #[test]
+    fn render_pipes_replaces_keys_only() {
+        let out = render_pipes("Hi {{ name }}, {{not a key}} {{");
+        assert_eq!(out, "Hi <span class=\"sqe-pipe\" data-sqe-pipe=\"name\"></span>, {{not a key}} {{");
+    }
+
     #[test]
     fn escape_html_basic() {
         let raw = "<a & '\">";
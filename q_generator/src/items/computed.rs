@@ -0,0 +1,148 @@
+// === src/items/computed.rs ===
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+
+use crate::expr::{self, Expr};
+use crate::items::common::is_key;
+use crate::items::value::OptionValue;
+use crate::responses::Answers;
+
+/// Document-level computed variable: `let NAME = <expression>`. The runtime recalculates
+/// it whenever an answer changes and saves it with the answers; conditions, piping
+/// (`{{name}}`) and scales can use it like an answer key.
+///
+/// ```text
+/// let bmi = round(weight / (height / 100) ^ 2, 1)
+/// ```
+#[derive(Debug, Clone)]
+pub struct Computed {
+    pub name: String,
+    /// Expression as written
+    pub source: String,
+    pub expr: Expr,
+}
+
+impl Computed {
+    /// Parse the text after `let`: `NAME = <expression>`.
+    pub fn parse(rest: &str) -> Result<Self, String> {
+        let (name, source) = rest
+            .split_once('=')
+            .ok_or_else(|| format!("`let {}` needs `= <expression>`", rest.trim()))?;
+        let name = name.trim();
+        if !is_key(name) || name.starts_with(|c: char| c.is_ascii_digit()) {
+            return Err(format!("invalid variable name `{}`", name));
+        }
+        let source = source.trim();
+        let expr = expr::parse(source).map_err(|e| format!("let {}: {}", name, e))?;
+        Ok(Computed {
+            name: name.to_string(),
+            source: source.to_string(),
+            expr,
+        })
+    }
+}
+
+/// Order computed variables so each comes after the variables it reads. Every reference
+/// must be a key in `known` or another computed variable; cycles, two variables with
+/// one name and a variable named like an answer key are errors.
+pub fn evaluation_order<'a>(computed: &[&'a Computed], known: &HashSet<String>) -> Result<Vec<&'a Computed>, String> {
+    let mut by_name: HashMap<&str, &'a Computed> = HashMap::new();
+    for c in computed {
+        if by_name.insert(c.name.as_str(), *c).is_some() {
+            return Err(format!("let {}: the variable is defined twice", c.name));
+        }
+    }
+    for c in computed {
+        if known.contains(&c.name) {
+            return Err(format!("let {}: a question already uses the key `{}`", c.name, c.name));
+        }
+        if let Some(name) = c.expr.references().into_iter().find(|r| !known.contains(*r) && !by_name.contains_key(r)) {
+            return Err(format!("let {}: `{}` is not an answer key or computed variable", c.name, name));
+        }
+    }
+
+    // depth-first topological sort; `path` holds the variables being visited
+    fn visit<'a>(
+        c: &'a Computed,
+        by_name: &HashMap<&str, &'a Computed>,
+        done: &mut Vec<&'a Computed>,
+        path: &mut Vec<&'a str>,
+    ) -> Result<(), String> {
+        if done.iter().any(|d| d.name == c.name) {
+            return Ok(());
+        }
+        if let Some(start) = path.iter().position(|p| *p == c.name) {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(&c.name);
+            return Err(format!("computed variables depend on each other: {}", cycle.join(" -> ")));
+        }
+        path.push(&c.name);
+        for r in c.expr.references() {
+            if let Some(dep) = by_name.get(r) {
+                visit(dep, by_name, done, path)?;
+            }
+        }
+        path.pop();
+        done.push(c);
+        Ok(())
+    }
+
+    let mut done = Vec::new();
+    for c in computed {
+        visit(c, &by_name, &mut done, &mut Vec::new())?;
+    }
+    Ok(done)
+}
+
+/// Answers with the computed variables filled in, for responses saved without them.
+/// Values saved by the runtime are kept as they are. `ordered` must be in
+/// [`evaluation_order`]; answers holding one of their missing-value `codes` read as null.
+pub fn with_computed<'a>(ordered: &[&Computed], codes: &HashMap<String, Vec<OptionValue>>, answers: &'a Answers) -> Cow<'a, Answers> {
+    if ordered.iter().all(|c| answers.contains_key(&c.name)) {
+        return Cow::Borrowed(answers);
+    }
+    let mut filled = answers.clone();
+    for c in ordered {
+        if !filled.contains_key(&c.name) {
+            let value = c.expr.eval_with_codes(&filled, codes);
+            filled.insert(c.name.clone(), value);
+        }
+    }
+    Cow::Owned(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn orders_and_rejects_undefined_and_cycles() {
+        let known: HashSet<String> = ["weight", "height"].iter().map(|s| s.to_string()).collect();
+        let bmi_class = Computed::parse("obese = bmi >= 30").unwrap();
+        let bmi = Computed::parse(" bmi = weight / (height / 100) ^ 2").unwrap();
+        let order = evaluation_order(&[&bmi_class, &bmi], &known).unwrap();
+        assert_eq!(order.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), vec!["bmi", "obese"]);
+
+        let undefined = Computed::parse("x = age + 1").unwrap();
+        assert!(evaluation_order(&[&undefined], &known).unwrap_err().contains("`age`"));
+
+        let a = Computed::parse("a = b + 1").unwrap();
+        let b = Computed::parse("b = a * 2").unwrap();
+        assert!(evaluation_order(&[&a, &b], &known).unwrap_err().contains("a -> b -> a"));
+
+        assert!(Computed::parse("1x = 2").is_err());
+        assert!(Computed::parse("x 2").is_err());
+    }
+
+    #[test]
+    fn rejects_duplicate_names() {
+        let known: HashSet<String> = ["weight"].iter().map(|s| s.to_string()).collect();
+        let first = Computed::parse("x = weight + 1").unwrap();
+        let second = Computed::parse("x = weight * 2").unwrap();
+        assert!(evaluation_order(&[&first, &second], &known).unwrap_err().contains("defined twice"));
+
+        let shadow = Computed::parse("weight = 70").unwrap();
+        assert!(evaluation_order(&[&shadow], &known).unwrap_err().contains("already uses the key `weight`"));
+    }
+}
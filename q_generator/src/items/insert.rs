@@ -1,6 +1,6 @@
 // === src/items/insert.rs ===
 
-use crate::items::common::escape_html;
+use crate::items::common::{escape_html, render_pipes};
 
 #[derive(Debug, Clone)]
 pub struct Insert {
@@ -20,7 +20,7 @@ impl Insert {
         let lines: Vec<String> = self
             .text
             .lines()
-            .map(|l| escape_html(l))
+            .map(|line| render_pipes(&escape_html(line)))
             .collect();
         let joined = lines.join("<br/>\n");
         let html = format!("<div class=\"text-block\">{}</div>", joined);
@@ -0,0 +1,180 @@
+// === src/items/missing.rs ===
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::items::value::OptionValue;
+use crate::responses::Answers;
+
+/// Why an answer is missing. The runtime writes `not_answered` and `not_applicable`
+/// itself; `dont_know` and `refused` are extra options the respondent can choose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MissingKind {
+    /// Left blank
+    NotAnswered,
+    /// Hidden by a `.show if` condition
+    NotApplicable,
+    DontKnow,
+    Refused,
+}
+
+impl MissingKind {
+    pub const ALL: [MissingKind; 4] =
+        [MissingKind::NotAnswered, MissingKind::NotApplicable, MissingKind::DontKnow, MissingKind::Refused];
+
+    pub fn parse(s: &str) -> Option<MissingKind> {
+        MissingKind::ALL.into_iter().find(|k| k.name() == s)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MissingKind::NotAnswered => "not_answered",
+            MissingKind::NotApplicable => "not_applicable",
+            MissingKind::DontKnow => "dont_know",
+            MissingKind::Refused => "refused",
+        }
+    }
+
+    pub fn default_label(self) -> &'static str {
+        match self {
+            MissingKind::NotAnswered => "Not answered",
+            MissingKind::NotApplicable => "Not applicable",
+            MissingKind::DontKnow => "Don't know",
+            MissingKind::Refused => "Prefer not to answer",
+        }
+    }
+
+    /// Whether the code is offered to the respondent as an option.
+    pub fn is_option(self) -> bool {
+        matches!(self, MissingKind::DontKnow | MissingKind::Refused)
+    }
+}
+
+/// A missing-value code, declared for the whole document or for one question:
+///
+/// ```text
+/// missing not_answered >> -9               // document level: every question
+/// missing refused >> -99                   // document level: the code questions use
+///
+/// choice income {
+///     ...
+///     .missing refused                     // offer "Prefer not to answer" (-99)
+///     .missing dont_know Not sure >> -98   // own label and code
+/// }
+/// ```
+///
+/// Codes are stored in the answers like any value, but scoring, reports and
+/// expressions treat them as unanswered.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MissingCode {
+    pub kind: MissingKind,
+    /// Option label; the kind's default label when not given
+    pub label: Option<String>,
+    /// The stored code; a question's code may come from the document-level declaration
+    pub value: Option<OptionValue>,
+}
+
+impl MissingCode {
+    /// Parse the text after `missing`: `<kind> [label] [>> code]`.
+    pub fn parse(rest: &str) -> Result<Self, String> {
+        let (head, value) = match rest.split_once(">>") {
+            Some((head, value)) => (head.trim(), Some(OptionValue::parse(value))),
+            None => (rest.trim(), None),
+        };
+        let (kind, label) = head.split_once(char::is_whitespace).unwrap_or((head, ""));
+        let kind = MissingKind::parse(kind).ok_or_else(|| {
+            format!("unknown missing-value kind `{}` (use not_answered, not_applicable, dont_know or refused)", kind)
+        })?;
+        let label = label.trim();
+        if matches!(&value, Some(OptionValue::String(s)) if s.is_empty()) {
+            return Err(format!("missing {}: empty code", kind.name()));
+        }
+        Ok(MissingCode {
+            kind,
+            label: (!label.is_empty()).then(|| label.to_string()),
+            value,
+        })
+    }
+
+    pub fn label(&self) -> &str {
+        self.label.as_deref().unwrap_or(self.kind.default_label())
+    }
+}
+
+/// The codes of one question: its own declarations, with codes they leave out taken
+/// from the document, plus the document's `not_answered` and `not_applicable` codes.
+/// `dont_know` and `refused` apply only where a question declares them.
+pub fn resolve(own: &[MissingCode], document: &[&MissingCode]) -> Result<Vec<MissingCode>, String> {
+    let mut codes: Vec<MissingCode> = Vec::new();
+    for kind in MissingKind::ALL {
+        let doc = document.iter().rev().find(|c| c.kind == kind);
+        let code = match own.iter().rev().find(|c| c.kind == kind) {
+            Some(c) => {
+                let value = c.value.clone().or_else(|| doc.and_then(|d| d.value.clone())).ok_or_else(|| {
+                    format!(".missing {} needs a code: `>> -99` here or `missing {} >> -99` in the document", kind.name(), kind.name())
+                })?;
+                MissingCode {
+                    kind,
+                    label: c.label.clone().or_else(|| doc.and_then(|d| d.label.clone())),
+                    value: Some(value),
+                }
+            }
+            None => match doc {
+                Some(d) if !kind.is_option() => (*d).clone(),
+                _ => continue,
+            },
+        };
+        if let Some(other) = codes.iter().find(|c| c.value == code.value) {
+            return Err(format!("missing {} and {} share the code `{}`", other.kind.name(), kind.name(), code.value.as_ref().map(|v| v.to_string()).unwrap_or_default()));
+        }
+        codes.push(code);
+    }
+    Ok(codes)
+}
+
+/// Answers with every missing-value code (`codes` per answer key) replaced by null, as
+/// scoring and expressions see them.
+pub fn without_codes<'a>(codes: &HashMap<String, Vec<OptionValue>>, answers: &'a Answers) -> Cow<'a, Answers> {
+    let is_code = |key: &String, v: &Value| codes.get(key).is_some_and(|cs| cs.iter().any(|c| c.matches(v)));
+    if !answers.iter().any(|(k, v)| is_code(k, v)) {
+        return Cow::Borrowed(answers);
+    }
+    let mut cleaned = answers.clone();
+    for (k, v) in cleaned.iter_mut() {
+        if is_code(k, v) {
+            *v = Value::Null;
+        }
+    }
+    Cow::Owned(cleaned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_resolves_codes() {
+        let code = MissingCode::parse("dont_know Not sure >> -98").unwrap();
+        assert_eq!(code.kind, MissingKind::DontKnow);
+        assert_eq!(code.label(), "Not sure");
+        assert_eq!(code.value, Some(OptionValue::Integer(-98)));
+        assert!(MissingCode::parse("skipped >> -1").is_err());
+
+        let not_answered = MissingCode::parse("not_answered >> -9").unwrap();
+        let refused = MissingCode::parse("refused >> -99").unwrap();
+        let document = [&not_answered, &refused];
+        // document-level refused is only offered where a question asks for it
+        let codes = resolve(&[], &document).unwrap();
+        assert_eq!(codes.iter().map(|c| c.kind).collect::<Vec<_>>(), vec![MissingKind::NotAnswered]);
+
+        let own = [MissingCode::parse("refused").unwrap()];
+        let codes = resolve(&own, &document).unwrap();
+        assert_eq!(codes[1].value, Some(OptionValue::Integer(-99)));
+        assert_eq!(codes[1].label(), "Prefer not to answer");
+
+        assert!(resolve(&[MissingCode::parse("dont_know").unwrap()], &document).unwrap_err().contains("needs a code"));
+        assert!(resolve(&[MissingCode::parse("dont_know >> -9").unwrap()], &document).unwrap_err().contains("share the code"));
+    }
+}
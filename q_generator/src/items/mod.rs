@@ -5,9 +5,25 @@ pub mod insert;
 pub mod html;
 pub mod js;
 pub mod css;
+pub mod submit;
+pub mod scale;
+pub mod computed;
+pub mod value;
+pub mod missing;
+pub mod quiz;
+pub mod bank;
+pub mod timer;
+pub mod navigation;
  
 pub use choose::Choose;
 pub use insert::Insert;
 pub use html::Html;
 pub use js::Js;
 pub use css::Css;
+pub use submit::Submit;
+pub use scale::Scale;
+pub use computed::Computed;
+pub use missing::MissingCode;
+pub use quiz::Quiz;
+pub use bank::{Bank, Draw};
+pub use navigation::Navigation;
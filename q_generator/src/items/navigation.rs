@@ -0,0 +1,122 @@
+// === src/items/navigation.rs ===
+
+use serde_json::{Value, json};
+
+use crate::transcompiler::Entry;
+
+/// Document-level navigation aids of the page runtime, one directive each:
+///
+/// ```text
+/// progress required   // progress bar over the pages (default) or the answered required questions
+/// toc dropdown        // contents of the @p titles as a sidebar (default) or a dropdown
+/// navigation free     // the contents jump to any page, not only to pages already visited
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Navigation {
+    Progress(Progress),
+    Toc(Toc),
+    Free,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Progress {
+    Pages,
+    Required,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Toc {
+    Sidebar,
+    Dropdown,
+}
+
+impl Progress {
+    pub fn name(self) -> &'static str {
+        match self {
+            Progress::Pages => "pages",
+            Progress::Required => "required",
+        }
+    }
+}
+
+impl Toc {
+    pub fn name(self) -> &'static str {
+        match self {
+            Toc::Sidebar => "sidebar",
+            Toc::Dropdown => "dropdown",
+        }
+    }
+}
+
+impl Navigation {
+    /// Parse a `progress`, `toc` or `navigation` line; `rest` is the text after the keyword.
+    pub fn parse(keyword: &str, rest: &str) -> Result<Self, String> {
+        let rest = rest.trim();
+        match (keyword, rest) {
+            ("progress", "" | "pages") => Ok(Navigation::Progress(Progress::Pages)),
+            ("progress", "required") => Ok(Navigation::Progress(Progress::Required)),
+            ("progress", _) => Err(format!("progress: unknown kind `{}` (use pages or required)", rest)),
+            ("toc", "" | "sidebar") => Ok(Navigation::Toc(Toc::Sidebar)),
+            ("toc", "dropdown") => Ok(Navigation::Toc(Toc::Dropdown)),
+            ("toc", _) => Err(format!("toc: unknown style `{}` (use sidebar or dropdown)", rest)),
+            ("navigation", "free") => Ok(Navigation::Free),
+            ("navigation", _) => Err(format!("navigation: unknown mode `{}` (only `navigation free` exists)", rest)),
+            _ => Err(format!("unknown directive `{}`", keyword)),
+        }
+    }
+}
+
+/// The navigation directives of a document taken together; later ones override earlier ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NavigationSettings {
+    pub progress: Option<Progress>,
+    pub toc: Option<Toc>,
+    pub free: bool,
+}
+
+impl NavigationSettings {
+    pub fn of(ast: &[Entry]) -> Self {
+        let mut settings = NavigationSettings::default();
+        for entry in ast {
+            match entry {
+                Entry::Navigation(Navigation::Progress(p)) => settings.progress = Some(*p),
+                Entry::Navigation(Navigation::Toc(t)) => settings.toc = Some(*t),
+                Entry::Navigation(Navigation::Free) => settings.free = true,
+                _ => {}
+            }
+        }
+        settings
+    }
+
+    /// `{progress, toc, free}` with the kinds by name, as used by the runtime and exports.
+    pub fn to_json(self) -> Value {
+        json!({
+            "progress": self.progress.map(Progress::name),
+            "toc": self.toc.map(Toc::name),
+            "free": self.free,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_directives() {
+        assert_eq!(Navigation::parse("progress", ""), Ok(Navigation::Progress(Progress::Pages)));
+        assert_eq!(Navigation::parse("progress", " required"), Ok(Navigation::Progress(Progress::Required)));
+        assert_eq!(Navigation::parse("toc", "dropdown"), Ok(Navigation::Toc(Toc::Dropdown)));
+        assert_eq!(Navigation::parse("navigation", "free"), Ok(Navigation::Free));
+        assert!(Navigation::parse("toc", "top").is_err());
+        assert!(Navigation::parse("navigation", "").is_err());
+
+        let ast = vec![
+            Entry::Navigation(Navigation::Toc(Toc::Sidebar)),
+            Entry::Navigation(Navigation::Toc(Toc::Dropdown)),
+            Entry::Navigation(Navigation::Free),
+        ];
+        let settings = NavigationSettings::of(&ast);
+        assert_eq!((settings.progress, settings.toc, settings.free), (None, Some(Toc::Dropdown), true));
+    }
+}
@@ -6,7 +6,7 @@ pub fn return_qrcode(web_address: &str) -> Result<String, Box<dyn std::error::Er
     use qrcode::render::unicode::Dense1x2;
 
     // Convert the qrcode crate error into a boxed error for our return type
-    let code = QrCode::new(web_address).map_err(|e| Box::<dyn std::error::Error>::from(e))?;
+    let code = QrCode::new(web_address).map_err(Box::<dyn std::error::Error>::from)?;
     let qr_string = code.render::<Dense1x2>().module_dimensions(2, 1).build();
     Ok(qr_string)
 }
@@ -0,0 +1,47 @@
+// === src/items/quiz.rs ===
+
+/// Document-level `mode quiz`: grade the choices that mark correct options or points.
+///
+/// ```text
+/// mode quiz                   // grade once, when the answers are saved
+/// mode quiz per-page reveal   // also grade each page on "Next"; show the correct options
+/// ```
+///
+/// Options are marked on the choice itself:
+///
+/// ```text
+/// choice capital {
+///     Capital of France?
+///     Paris >> 1 .correct .feedback Right, Paris since 987.
+///     Lyon >> 2 .feedback Lyon is the third-largest city.
+///     Both >> 3 .points 0.5
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Quiz {
+    /// Grade each page when the respondent moves on
+    pub per_page: bool,
+    /// Mark the correct options once a question is graded
+    pub reveal: bool,
+}
+
+impl Quiz {
+    /// Parse the text after `mode`: `quiz [per-page] [reveal]`.
+    pub fn parse(rest: &str) -> Result<Self, String> {
+        let mut words = rest.split_whitespace();
+        match words.next() {
+            Some("quiz") => {}
+            Some(other) => return Err(format!("unknown mode `{}` (only `mode quiz` exists)", other)),
+            None => return Err("`mode` needs a name, e.g. `mode quiz`".to_string()),
+        }
+        let mut quiz = Quiz::default();
+        for word in words {
+            match word {
+                "per-page" => quiz.per_page = true,
+                "reveal" => quiz.reveal = true,
+                _ => return Err(format!("mode quiz: unknown option `{}` (use per-page or reveal)", word)),
+            }
+        }
+        Ok(quiz)
+    }
+}
@@ -0,0 +1,124 @@
+// === src/items/scale.rs ===
+
+use crate::norms::NormTable;
+
+/// One item of a scale: the answer key it reads and whether it is reverse keyed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScaleItem {
+    pub key: String,
+    pub reversed: bool,
+}
+
+/// How item values are combined into the scale score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Method {
+    #[default]
+    Sum,
+    Mean,
+}
+
+/// What a sum does with unanswered items.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Missing {
+    /// Sum the answered items only.
+    #[default]
+    Skip,
+    /// Scale the sum of the answered items up to the full item count.
+    Prorate,
+}
+
+/// Document-level `scale` declaration: a named group of answer keys that are scored
+/// together. A leading `-` marks a reverse-keyed item.
+///
+/// ```text
+/// scale extraversion {
+///     e1 e2 -e3
+///     e4, -e5
+///     method sum          // or mean
+///     missing prorate     // or skip (default)
+///     min-answered 4      // fewer answered items give no score (default 1)
+///     norms "norms/extraversion.csv" by age_group sex
+///     feedback yes        // show the score to the respondent after saving (default no)
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Scale {
+    pub name: String,
+    pub items: Vec<ScaleItem>,
+    pub method: Method,
+    pub missing: Missing,
+    pub min_answered: usize,
+    /// Norm table file and the answer keys it is stratified by, as written
+    pub norms_source: Option<(String, Vec<String>)>,
+    /// The loaded norm table (filled in by the compiler, relative to the source file)
+    pub norms: Option<NormTable>,
+    /// Show the score (and norms) on the results screen of the generated page
+    pub feedback: bool,
+}
+
+impl Scale {
+    /// Parse the scale name (the text between `scale` and `{`) and its block. Items may
+    /// be separated by whitespace, commas or line breaks.
+    pub fn parse(name: &str, block: &str) -> Self {
+        let mut scale = Scale {
+            name: name.trim().trim_matches('"').to_string(),
+            items: Vec::new(),
+            method: Method::default(),
+            missing: Missing::default(),
+            min_answered: 1,
+            norms_source: None,
+            norms: None,
+            feedback: false,
+        };
+        for line in block.lines().map(|l| l.split("//").next().unwrap_or("").trim()) {
+            let (keyword, value) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let value = value.trim();
+            match keyword {
+                "method" => scale.method = if value == "mean" { Method::Mean } else { Method::Sum },
+                "missing" => scale.missing = if value == "prorate" { Missing::Prorate } else { Missing::Skip },
+                "min-answered" => scale.min_answered = value.parse().unwrap_or(1),
+                "feedback" => scale.feedback = value == "yes",
+                "norms" => {
+                    let (file, by) = match value.strip_prefix('"') {
+                        Some(quoted) => quoted.split_once('"').unwrap_or((quoted, "")),
+                        None => value.split_once(char::is_whitespace).unwrap_or((value, "")),
+                    };
+                    let by = match by.trim().split_once(char::is_whitespace) {
+                        Some(("by", keys)) => keys,
+                        _ => by,
+                    };
+                    scale.norms_source = Some((file.to_string(), by.split_whitespace().map(String::from).collect()));
+                }
+                _ => scale.items.extend(
+                    line.split(|c: char| c.is_whitespace() || c == ',')
+                        .filter(|t| !t.is_empty())
+                        .map(|t| match t.strip_prefix('-') {
+                            Some(key) => ScaleItem { key: key.to_string(), reversed: true },
+                            None => ScaleItem { key: t.to_string(), reversed: false },
+                        })
+                        .filter(|item| !item.key.is_empty()),
+                ),
+            }
+        }
+        scale
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_items_and_reverse_keying() {
+        let s = Scale::parse(" extraversion ", "e1 e2 -e3 // comment\n  e4,-e5\nmethod mean\nmin-answered 3\n");
+        assert_eq!(s.name, "extraversion");
+        let keys: Vec<(&str, bool)> = s.items.iter().map(|i| (i.key.as_str(), i.reversed)).collect();
+        assert_eq!(keys, vec![("e1", false), ("e2", false), ("e3", true), ("e4", false), ("e5", true)]);
+        assert_eq!(s.method, Method::Mean);
+        assert_eq!(s.missing, Missing::Skip);
+        assert_eq!(s.min_answered, 3);
+
+        let normed = Scale::parse("n", "a b\nnorms \"norms/n.csv\" by age sex");
+        assert_eq!(normed.norms_source, Some(("norms/n.csv".to_string(), vec!["age".to_string(), "sex".to_string()])));
+    }
+}
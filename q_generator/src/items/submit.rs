@@ -0,0 +1,169 @@
+// === src/items/submit.rs ===
+
+use crate::items::common::to_js_string;
+
+/// Endpoint used by `submit` without a URL: the collection endpoint of `sqe serve`.
+pub const DEFAULT_ENDPOINT: &str = "/api/responses";
+
+/// Document-level `submit` directive: where the save button sends the answers.
+///
+/// ```text
+/// submit                      // POST to the local `serve` collection endpoint
+/// submit "https://example.org/collect" {
+///     method PUT
+///     header Authorization "Bearer abc123"
+///     retry 3                 // extra attempts before giving up (default 2)
+///     queue off               // do not keep failed payloads in localStorage
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Submit {
+    pub url: String,
+    pub method: String,
+    pub headers: Vec<(String, String)>,
+    /// Number of retries after the first failed attempt.
+    pub retries: u32,
+    /// Keep unsent payloads in localStorage and resend them when back online.
+    pub queue: bool,
+}
+
+/// Read a value that may be quoted (`"a b"`) or a single bare word.
+fn unquote(s: &str) -> String {
+    let s = s.trim();
+    if s.starts_with('"') {
+        s.split('"').nth(1).unwrap_or("").to_string()
+    } else {
+        s.to_string()
+    }
+}
+
+/// Drop a trailing ` // comment` that is not inside quotes.
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    let mut prev = ' ';
+    for (i, ch) in line.char_indices() {
+        match ch {
+            '"' => quoted = !quoted,
+            '/' if !quoted && prev.is_whitespace() && line[i..].starts_with("//") => return line[..i].trim_end(),
+            _ => {}
+        }
+        prev = ch;
+    }
+    line
+}
+
+impl Submit {
+    /// Parse the text after the `submit` keyword (up to any `{`) and the optional
+    /// option block. Unknown options and bad values are errors.
+    pub fn parse(rest: &str, block: Option<&str>) -> Result<Self, String> {
+        let rest = rest.trim();
+        let url = if rest.starts_with('"') {
+            unquote(rest)
+        } else {
+            rest.split_whitespace().next().unwrap_or("").to_string()
+        };
+        let mut submit = Submit {
+            url: if url.is_empty() { DEFAULT_ENDPOINT.to_string() } else { url },
+            method: "POST".to_string(),
+            headers: Vec::new(),
+            retries: 2,
+            queue: true,
+        };
+
+        for ln in block.unwrap_or("").lines().map(|l| strip_comment(l.trim())) {
+            if ln.is_empty() {
+                continue;
+            }
+            let (key, value) = ln.split_once(char::is_whitespace).unwrap_or((ln, ""));
+            let value = value.trim();
+            match key {
+                "method" => {
+                    // fetch refuses a GET or HEAD with a body, so only methods that send one
+                    let method = unquote(value).to_ascii_uppercase();
+                    if !matches!(method.as_str(), "POST" | "PUT" | "PATCH") {
+                        return Err(format!("submit: unsupported method `{}` (use POST, PUT or PATCH)", value));
+                    }
+                    submit.method = method;
+                }
+                "header" => {
+                    let (name, val) = value.split_once(char::is_whitespace).unwrap_or((value, ""));
+                    let name = unquote(name.trim_end_matches(':'));
+                    if name.is_empty() {
+                        return Err("submit: `header` needs a name, e.g. `header Authorization \"Bearer abc\"`".to_string());
+                    }
+                    submit.headers.push((name, unquote(val)));
+                }
+                "retry" => {
+                    submit.retries = value
+                        .parse()
+                        .map_err(|_| format!("submit: retry `{}` is not a number of attempts", value))?;
+                }
+                "queue" => {
+                    submit.queue = match value {
+                        "on" | "true" | "yes" => true,
+                        "off" | "false" | "no" => false,
+                        _ => return Err(format!("submit: queue `{}` (use on or off)", value)),
+                    }
+                }
+                _ => return Err(format!("submit: unknown option `{}` (use method, header, retry or queue)", key)),
+            }
+        }
+        Ok(submit)
+    }
+
+    /// JS object literal consumed by the runtime's save handler.
+    pub fn to_js_config(&self) -> String {
+        let headers: Vec<String> = self
+            .headers
+            .iter()
+            .map(|(k, v)| format!("{}:{}", to_js_string(k), to_js_string(v)))
+            .collect();
+        format!(
+            "{{\"url\":{},\"method\":{},\"headers\":{{{}}},\"retries\":{},\"queue\":{}}}",
+            to_js_string(&self.url),
+            to_js_string(&self.method),
+            headers.join(","),
+            self.retries,
+            self.queue
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_options_block() {
+        let s = Submit::parse(
+            " \"http://localhost:8000/api/responses\" ",
+            Some("method put\nheader Authorization \"Bearer x y\"\nheader X-Study: s1\nretry 5\nqueue off\n"),
+        )
+        .unwrap();
+        assert_eq!(s.url, "http://localhost:8000/api/responses");
+        assert_eq!(s.method, "PUT");
+        assert_eq!(
+            s.headers,
+            vec![
+                ("Authorization".to_string(), "Bearer x y".to_string()),
+                ("X-Study".to_string(), "s1".to_string())
+            ]
+        );
+        assert_eq!(s.retries, 5);
+        assert!(!s.queue);
+
+        let bare = Submit::parse("", None).unwrap();
+        assert_eq!(bare.url, DEFAULT_ENDPOINT);
+        assert_eq!(bare.method, "POST");
+        assert!(bare.queue);
+    }
+
+    #[test]
+    fn rejects_bad_options() {
+        for block in ["retires 3", "retry abc", "queue maybe", "method GET", "method", "header"] {
+            assert!(Submit::parse("", Some(block)).is_err(), "{}", block);
+        }
+        let s = Submit::parse("", Some("method patch // partial update\n// note\nheader X-Ref \"a //b\"\nqueue on")).unwrap();
+        assert_eq!((s.method.as_str(), s.headers[0].1.as_str()), ("PATCH", "a //b"));
+    }
+}
@@ -0,0 +1,41 @@
+// === src/items/timer.rs ===
+
+/// Parse the text after `timer`: a page's time limit in seconds, written as `90`,
+/// `90s`, `2m` or `1m30s`.
+///
+/// ```text
+/// @p Quick round
+/// timer 1m30s     // countdown; when it runs out the page is locked and the next one shown
+/// ```
+pub fn parse_time_limit(rest: &str) -> Result<u32, String> {
+    let text = rest.trim();
+    let invalid = || format!("timer: `{}` is not a duration (use e.g. 90, 90s, 2m or 1m30s)", text);
+    let (minutes, seconds) = match text.split_once('m') {
+        Some((m, s)) => (m, s),
+        None => ("0", text),
+    };
+    let minutes: u32 = minutes.parse().map_err(|_| invalid())?;
+    let seconds = seconds.strip_suffix('s').unwrap_or(seconds);
+    let seconds: u32 = if seconds.is_empty() && text.contains('m') { 0 } else { seconds.parse().map_err(|_| invalid())? };
+    let total = minutes.checked_mul(60).and_then(|m| m.checked_add(seconds)).ok_or_else(invalid)?;
+    if total == 0 {
+        return Err("timer: the time limit must be at least 1 second".to_string());
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_time_limit("90"), Ok(90));
+        assert_eq!(parse_time_limit(" 45s "), Ok(45));
+        assert_eq!(parse_time_limit("2m"), Ok(120));
+        assert_eq!(parse_time_limit("1m30s"), Ok(90));
+        assert!(parse_time_limit("0").is_err());
+        assert!(parse_time_limit("soon").is_err());
+        assert!(parse_time_limit("").is_err());
+    }
+}
@@ -0,0 +1,206 @@
+// === src/items/value.rs ===
+
+use std::fmt;
+
+use serde_json::{Value, json};
+
+use crate::scoring::number;
+
+/// Type of a question's option values, declared with `.type <name>` or inferred from
+/// the values. Integers and numbers mix freely (as `number`); other types do not.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueType {
+    Integer,
+    Number,
+    Boolean,
+    String,
+}
+
+impl ValueType {
+    pub fn parse(s: &str) -> Option<ValueType> {
+        match s.trim() {
+            "integer" | "int" => Some(ValueType::Integer),
+            "number" | "float" => Some(ValueType::Number),
+            "boolean" | "bool" => Some(ValueType::Boolean),
+            "string" | "text" => Some(ValueType::String),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ValueType::Integer => "integer",
+            ValueType::Number => "number",
+            ValueType::Boolean => "boolean",
+            ValueType::String => "string",
+        }
+    }
+
+    /// Whether a value of type `other` may stand in a list of this type.
+    fn accepts(self, other: ValueType) -> bool {
+        self == other || (self == ValueType::Number && other == ValueType::Integer)
+    }
+
+    /// The type of a list holding values of both types, if they may mix.
+    fn join(self, other: ValueType) -> Option<ValueType> {
+        if self.accepts(other) {
+            Some(self)
+        } else if other.accepts(self) {
+            Some(other)
+        } else {
+            None
+        }
+    }
+}
+
+/// An option value as written after `>>`: `"7"` (quoted) is a string, `true`/`false`
+/// a boolean, `3` an integer, `2.5` a number and any other bare word, including codes
+/// with leading zeros such as `007`, a string.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OptionValue {
+    Integer(i64),
+    Number(f64),
+    Boolean(bool),
+    String(String),
+}
+
+impl OptionValue {
+    /// Infer the value from its source text.
+    pub fn parse(text: &str) -> OptionValue {
+        let text = text.trim();
+        if let Some(inner) = text.strip_prefix('"').and_then(|t| t.strip_suffix('"')) {
+            return OptionValue::String(inner.to_string());
+        }
+        match text {
+            "true" => return OptionValue::Boolean(true),
+            "false" => return OptionValue::Boolean(false),
+            _ => {}
+        }
+        // a leading zero ("007") is a code, not a number
+        let digits = text.strip_prefix('-').unwrap_or(text);
+        if digits.len() > 1 && digits.starts_with('0') && !digits.starts_with("0.") {
+            return OptionValue::String(text.to_string());
+        }
+        if let Ok(i) = text.parse::<i64>() {
+            return OptionValue::Integer(i);
+        }
+        // plain decimal notation only, so "1e3", "inf" or "NaN" stay strings
+        if text.contains(|c: char| c.is_ascii_digit())
+            && text.chars().all(|c| c.is_ascii_digit() || c == '.' || c == '-')
+            && let Ok(f) = text.parse::<f64>()
+        {
+            return OptionValue::Number(f);
+        }
+        OptionValue::String(text.to_string())
+    }
+
+    /// Read the source text as the declared type. Text that does not fit is inferred
+    /// as usual, so [`check_values`] reports it.
+    pub fn parse_as(text: &str, declared: ValueType) -> OptionValue {
+        let trimmed = text.trim();
+        match (declared, OptionValue::parse(text)) {
+            (ValueType::String, OptionValue::String(s)) => OptionValue::String(s),
+            (ValueType::String, _) => OptionValue::String(trimmed.to_string()),
+            (ValueType::Number, OptionValue::Integer(i)) => OptionValue::Number(i as f64),
+            (_, inferred) => inferred,
+        }
+    }
+
+    pub fn value_type(&self) -> ValueType {
+        match self {
+            OptionValue::Integer(_) => ValueType::Integer,
+            OptionValue::Number(_) => ValueType::Number,
+            OptionValue::Boolean(_) => ValueType::Boolean,
+            OptionValue::String(_) => ValueType::String,
+        }
+    }
+
+    /// The value as stored in `answers.json`.
+    pub fn to_json(&self) -> Value {
+        match self {
+            OptionValue::Integer(i) => json!(i),
+            OptionValue::Number(f) => number(*f),
+            OptionValue::Boolean(b) => json!(b),
+            OptionValue::String(s) => json!(s),
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            OptionValue::Integer(i) => Some(*i as f64),
+            OptionValue::Number(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    /// Whether a stored answer is this value (numbers compare numerically).
+    pub fn matches(&self, answer: &Value) -> bool {
+        match (self.as_f64(), answer.as_f64()) {
+            (Some(a), Some(b)) => a == b,
+            _ => self.to_json() == *answer,
+        }
+    }
+}
+
+/// Text form: the `data-sqe-value` attribute and the keys of label maps.
+impl fmt::Display for OptionValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptionValue::String(s) => write!(f, "{}", s),
+            other => write!(f, "{}", other.to_json()),
+        }
+    }
+}
+
+/// Type of an option list: the declared type, else the common type of the values.
+/// Values of different types (other than integers among numbers) are an error.
+pub fn check_values<'a>(
+    values: impl IntoIterator<Item = &'a OptionValue>,
+    declared: Option<ValueType>,
+) -> Result<ValueType, String> {
+    let mut list_type = declared;
+    for value in values {
+        let t = value.value_type();
+        list_type = match list_type {
+            None => Some(t),
+            Some(lt) if declared.is_some() => {
+                if !lt.accepts(t) {
+                    return Err(format!("option value `{}` is not of type {}", value, lt.name()));
+                }
+                Some(lt)
+            }
+            Some(lt) => Some(lt.join(t).ok_or_else(|| {
+                format!(
+                    "options mix {} and {} values (`{}`); quote strings or declare `.type`",
+                    lt.name(),
+                    t.name(),
+                    value
+                )
+            })?),
+        };
+    }
+    Ok(list_type.unwrap_or(ValueType::Integer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn infers_and_declares_types() {
+        assert_eq!(OptionValue::parse("\"007\""), OptionValue::String("007".into()));
+        assert_eq!(OptionValue::parse("007"), OptionValue::String("007".into()));
+        assert_eq!(OptionValue::parse("0"), OptionValue::Integer(0));
+        assert_eq!(OptionValue::parse("1e3"), OptionValue::String("1e3".into()));
+        assert_eq!(OptionValue::parse("-2.5"), OptionValue::Number(-2.5));
+        assert_eq!(OptionValue::parse("true"), OptionValue::Boolean(true));
+        assert_eq!(OptionValue::parse_as("007", ValueType::String).to_json(), json!("007"));
+        assert_eq!(OptionValue::parse_as("3", ValueType::Number).to_json(), json!(3));
+
+        let mixed = [OptionValue::parse("1"), OptionValue::parse("yes")];
+        assert!(check_values(&mixed, None).unwrap_err().contains("mix integer and string"));
+        let numbers = [OptionValue::parse("1"), OptionValue::parse("1.5")];
+        assert_eq!(check_values(&numbers, None), Ok(ValueType::Number));
+        assert!(check_values(&numbers, Some(ValueType::Integer)).unwrap_err().contains("`1.5` is not of type integer"));
+    }
+}
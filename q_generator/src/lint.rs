@@ -0,0 +1,601 @@
+// === src/lint.rs ===
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde_json::{Value, json};
+
+use crate::export::{choice_items, computed, draws, scales};
+use crate::grading;
+use crate::items::choose::NO_QUESTION;
+use crate::items::common::pipe_keys;
+use crate::transcompiler::{Entry, Question};
+
+/// Severity of a lint finding. `Allow` disables the rule entirely.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Allow,
+    Warning,
+    Error,
+}
+
+impl Level {
+    pub fn parse(s: &str) -> Option<Level> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "allow" | "off" => Some(Level::Allow),
+            "warn" | "warning" => Some(Level::Warning),
+            "deny" | "error" => Some(Level::Error),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Level::Allow => "allow",
+            Level::Warning => "warning",
+            Level::Error => "error",
+        }
+    }
+}
+
+/// All known rules: (name, default level, short description).
+pub const RULES: &[(&str, Level, &str)] = &[
+    ("duplicate-id", Level::Error, "two questions share the same id"),
+    ("missing-id", Level::Warning, "choice without an id falls back to a positional key"),
+    ("duplicate-option-value", Level::Error, "two options of one choice share a value"),
+    ("empty-question", Level::Error, "choice has no question text"),
+    ("empty-page", Level::Warning, "page contains no items"),
+    ("undefined-answer-key", Level::Warning, "script reads an SQE_ANSWERS key no question defines"),
+    ("long-question", Level::Warning, "question stem is longer than the configured limit"),
+    ("undefined-pipe-key", Level::Warning, "`{{key}}` placeholder names no answer key or `let`"),
+    ("unknown-scale-item", Level::Error, "scale lists an answer key no choice question or `let` defines"),
+    ("ungraded-choice", Level::Warning, "choice in `mode quiz` marks no option `.correct` or `.points`"),
+    ("quiz-draw", Level::Warning, "`draw` in `mode quiz` puts the whole bank and its answers into the page"),
+];
+
+pub const DEFAULT_MAX_QUESTION_LENGTH: usize = 200;
+
+#[derive(Debug, Clone)]
+pub struct LintConfig {
+    levels: HashMap<&'static str, Level>,
+    pub max_question_length: usize,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        LintConfig {
+            levels: RULES.iter().map(|(name, lvl, _)| (*name, *lvl)).collect(),
+            max_question_length: DEFAULT_MAX_QUESTION_LENGTH,
+        }
+    }
+}
+
+impl LintConfig {
+    pub fn level(&self, rule: &str) -> Level {
+        self.levels.get(rule).copied().unwrap_or(Level::Allow)
+    }
+
+    /// Override the level of a single rule. Unknown rule names are rejected.
+    pub fn set_level(&mut self, rule: &str, level: Level) -> Result<(), String> {
+        match RULES.iter().find(|(name, _, _)| *name == rule) {
+            Some((name, _, _)) => {
+                self.levels.insert(name, level);
+                Ok(())
+            }
+            None => Err(format!("unknown lint rule `{}`", rule)),
+        }
+    }
+
+    /// Apply settings from a JSON config file of the form
+    /// `{ "rules": { "missing-id": "allow" }, "max-question-length": 120 }`.
+    pub fn load_file(&mut self, path: &Path) -> io::Result<()> {
+        let text = fs::read_to_string(path)?;
+        let v: Value = serde_json::from_str(&text)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+
+        if let Some(rules) = v.get("rules").and_then(|r| r.as_object()) {
+            for (rule, lvl) in rules {
+                let level = lvl
+                    .as_str()
+                    .and_then(Level::parse)
+                    .ok_or_else(|| invalid(format!("invalid level for rule `{}`", rule)))?;
+                self.set_level(rule, level).map_err(invalid)?;
+            }
+        }
+        if let Some(max) = v.get("max-question-length") {
+            self.max_question_length = max
+                .as_u64()
+                .ok_or_else(|| invalid("`max-question-length` must be a number".to_string()))?
+                as usize;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub rule: &'static str,
+    pub level: Level,
+    /// 0-based page index and page title, when the finding belongs to a page.
+    pub page: Option<(usize, String)>,
+    /// 1-based position of the item within its page.
+    pub item: Option<usize>,
+    pub message: String,
+}
+
+impl Finding {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "rule": self.rule,
+            "level": self.level.as_str(),
+            "page": self.page.as_ref().map(|(i, _)| i + 1),
+            "page_title": self.page.as_ref().map(|(_, t)| t.clone()),
+            "item": self.item,
+            "message": self.message,
+        })
+    }
+
+    pub fn to_human(&self) -> String {
+        let mut loc = String::new();
+        if let Some((i, title)) = &self.page {
+            loc.push_str(&format!("page {} \"{}\"", i + 1, title));
+        }
+        if let Some(item) = self.item {
+            if !loc.is_empty() {
+                loc.push_str(", ");
+            }
+            loc.push_str(&format!("item {}", item));
+        }
+        if loc.is_empty() {
+            format!("{}[{}]: {}", self.level.as_str(), self.rule, self.message)
+        } else {
+            format!("{}[{}]: {}: {}", self.level.as_str(), self.rule, loc, self.message)
+        }
+    }
+}
+
+struct Linter<'a> {
+    config: &'a LintConfig,
+    findings: Vec<Finding>,
+}
+
+impl Linter<'_> {
+    fn report(
+        &mut self,
+        rule: &'static str,
+        page: Option<(usize, &str)>,
+        item: Option<usize>,
+        message: String,
+    ) {
+        let level = self.config.level(rule);
+        if level == Level::Allow {
+            return;
+        }
+        self.findings.push(Finding {
+            rule,
+            level,
+            page: page.map(|(i, t)| (i, t.to_string())),
+            item,
+            message,
+        });
+    }
+}
+
+/// Run all enabled rules over a compiled questionnaire.
+pub fn lint(ast: &[Entry], config: &LintConfig) -> Vec<Finding> {
+    let mut linter = Linter {
+        config,
+        findings: Vec::new(),
+    };
+
+    let pages: Vec<(&str, &Vec<Question>)> = ast
+        .iter()
+        .filter_map(|e| match e {
+            Entry::Page { title, content, .. } => Some((title.as_str(), content)),
+            _ => None,
+        })
+        .collect();
+
+    // Keys that some question or script defines, and where each id was first seen.
+    let mut defined: HashSet<String> = computed(ast).into_iter().map(|c| c.name.clone()).collect();
+    let mut first_seen: HashMap<String, (usize, usize)> = HashMap::new();
+    // Keys read by scripts: (key, page, item)
+    let mut reads: Vec<(String, usize, usize)> = Vec::new();
+    // Keys shown through `{{key}}` placeholders: (key, page, item)
+    let mut pipes: Vec<(String, usize, usize)> = Vec::new();
+
+    for (pidx, (title, content)) in pages.iter().enumerate() {
+        if content.is_empty() {
+            linter.report("empty-page", Some((pidx, title)), None, "page has no items".to_string());
+        }
+
+        let mut q_local_idx = 0usize;
+        for (iidx, q) in content.iter().enumerate() {
+            let item = iidx + 1;
+            let here = Some((pidx, *title));
+            match q {
+                Question::Choose(_) | Question::Draw(_) => {
+                    // a draw renders every question of its pool
+                    for choose in q.choices() {
+                        let key = choose.store_key(pidx, q_local_idx);
+                        match &choose.id {
+                            Some(id) if !id.is_empty() => {
+                                if let Some((fp, fi)) = first_seen.get(id) {
+                                    let msg = format!(
+                                        "id `{}` is already used on page {}, item {}",
+                                        id,
+                                        fp + 1,
+                                        fi
+                                    );
+                                    linter.report("duplicate-id", here, Some(item), msg);
+                                } else {
+                                    first_seen.insert(id.clone(), (pidx, item));
+                                }
+                            }
+                            _ => {
+                                let msg = format!(
+                                    "choice has no id; answers are stored under positional key `{}`, which changes when questions move",
+                                    key
+                                );
+                                linter.report("missing-id", here, Some(item), msg);
+                            }
+                        }
+                        for (_, other) in choose.other_keys(&key) {
+                            if let Some((fp, fi)) = first_seen.get(&other) {
+                                let msg = format!("\"other\" text key `{}` is already used on page {}, item {}", other, fp + 1, fi);
+                                linter.report("duplicate-id", here, Some(item), msg);
+                            } else {
+                                first_seen.insert(other.clone(), (pidx, item));
+                            }
+                            defined.insert(other);
+                        }
+                        defined.insert(key);
+
+                        pipes.extend(pipe_keys(&choose.question).into_iter().map(|k| (k, pidx, item)));
+                        let question = choose.question.trim();
+                        if question.is_empty() || question == NO_QUESTION {
+                            linter.report("empty-question", here, Some(item), "choice has no question text".to_string());
+                        } else {
+                            let len = question.chars().count();
+                            if len > config.max_question_length {
+                                let msg = format!(
+                                    "question is {} characters long (limit {})",
+                                    len, config.max_question_length
+                                );
+                                linter.report("long-question", here, Some(item), msg);
+                            }
+                        }
+
+                        let mut values: HashSet<String> = HashSet::new();
+                        for (label, value) in &choose.options {
+                            if !values.insert(value.to_json().to_string()) {
+                                let msg = format!("option \"{}\" repeats value `{}`", label, value);
+                                linter.report("duplicate-option-value", here, Some(item), msg);
+                            }
+                        }
+
+                        let script = choose.script_lines.join("\n");
+                        scan_script(&script, &mut defined, &mut reads, pidx, item);
+                        q_local_idx += 1;
+                    }
+                    if let Question::Draw(d) = q {
+                        defined.insert(d.key.clone());
+                    }
+                }
+                Question::Js(node) => {
+                    scan_script(&node.script, &mut defined, &mut reads, pidx, item);
+                }
+                Question::Html(node) => {
+                    for key in attr_values(&node.html, "data-sqe-key") {
+                        defined.insert(key);
+                    }
+                }
+                Question::Insert(node) => {
+                    pipes.extend(pipe_keys(&node.text).into_iter().map(|k| (k, pidx, item)));
+                }
+                Question::Css(_) => {}
+            }
+        }
+    }
+
+    if grading::quiz(ast).is_some() {
+        for (pidx, key, choose) in choice_items(ast) {
+            if !choose.is_graded() {
+                let msg = format!("choice `{}` earns no points in the quiz (mark an option `.correct` or `.points`)", key);
+                linter.report("ungraded-choice", Some((pidx, pages[pidx].0)), None, msg);
+            }
+        }
+        // the page holds the whole pool of a draw, undrawn questions only hidden
+        for (pidx, d) in draws(ast) {
+            let msg = format!(
+                "`{}` ships all {} matching questions of bank `{}` with their correct options and feedback to every respondent (build with --hide-answers)",
+                d.key,
+                d.items.len(),
+                d.bank
+            );
+            linter.report("quiz-draw", Some((pidx, pages[pidx].0)), None, msg);
+        }
+    }
+
+    let mut choice_keys: HashSet<String> = choice_items(ast).into_iter().map(|(_, key, _)| key).collect();
+    choice_keys.extend(computed(ast).into_iter().map(|c| c.name.clone()));
+    for scale in scales(ast) {
+        for item in &scale.items {
+            if !choice_keys.contains(&item.key) {
+                let msg = format!(
+                    "scale `{}` lists `{}`, which is not a choice question or computed variable",
+                    scale.name, item.key
+                );
+                linter.report("unknown-scale-item", None, None, msg);
+            }
+        }
+    }
+
+    for (key, pidx, item) in pipes {
+        if !defined.contains(&key) {
+            let msg = format!("`{{{{{}}}}}` shows an answer key no question or `let` defines", key);
+            linter.report("undefined-pipe-key", Some((pidx, pages[pidx].0)), Some(item), msg);
+        }
+    }
+
+    for (key, pidx, item) in reads {
+        if !defined.contains(&key) {
+            let msg = format!("script reads SQE_ANSWERS key `{}`, which no question defines", key);
+            linter.report("undefined-answer-key", Some((pidx, pages[pidx].0)), Some(item), msg);
+        }
+    }
+
+    linter.findings
+}
+
+/// Record the SQE_ANSWERS keys a script reads and the ones it writes.
+fn scan_script(
+    script: &str,
+    defined: &mut HashSet<String>,
+    reads: &mut Vec<(String, usize, usize)>,
+    pidx: usize,
+    item: usize,
+) {
+    for (key, is_write) in answer_key_accesses(script) {
+        if is_write {
+            defined.insert(key);
+        } else {
+            reads.push((key, pidx, item));
+        }
+    }
+}
+
+/// Find literal answer keys accessed by a script. Recognises `SQE_ANSWERS["k"]`,
+/// `SQE_ANSWERS.k`, `SQE.getAnswer("k")`, `SQE.setAnswer("k", ...)` and local aliases
+/// such as `const a = window.SQE_ANSWERS || {}; a["k"]`. Returns `(key, is_write)`.
+pub fn answer_key_accesses(script: &str) -> Vec<(String, bool)> {
+    let mut roots: Vec<String> = vec!["SQE_ANSWERS".to_string()];
+    for decl in ["const ", "let ", "var "] {
+        let mut rest = script;
+        while let Some(pos) = rest.find(decl) {
+            let after = &rest[pos + decl.len()..];
+            let name: String = after.chars().take_while(|c| is_ident_char(*c)).collect();
+            rest = &after[name.len()..];
+            let tail = rest.trim_start();
+            if !name.is_empty()
+                && let Some(init) = tail.strip_prefix('=')
+            {
+                let init = init.trim_start();
+                let init = init.strip_prefix("window.").unwrap_or(init);
+                if let Some(after_root) = init.strip_prefix("SQE_ANSWERS")
+                    && !after_root.starts_with(['[', '.'])
+                {
+                    roots.push(name);
+                }
+            }
+        }
+    }
+
+    let mut out = Vec::new();
+    let bytes = script.as_bytes();
+    for root in &roots {
+        let mut start = 0;
+        while let Some(pos) = script[start..].find(root.as_str()) {
+            let at = start + pos;
+            let end = at + root.len();
+            start = end;
+            // whole-identifier match only
+            if at > 0 && is_ident_char(bytes[at - 1] as char) {
+                continue;
+            }
+            let rest = &script[end..];
+            let (key, after) = if let Some(idx) = rest.strip_prefix('[') {
+                match string_literal(idx.trim_start()) {
+                    Some((k, tail)) if tail.trim_start().starts_with(']') => {
+                        (k, tail.trim_start()[1..].to_string())
+                    }
+                    _ => continue,
+                }
+            } else if let Some(prop) = rest.strip_prefix('.') {
+                let name: String = prop.chars().take_while(|c| is_ident_char(*c)).collect();
+                let tail = &prop[name.len()..];
+                // method calls such as `a.hasOwnProperty(...)` are not answer reads
+                if name.is_empty() || tail.trim_start().starts_with('(') {
+                    continue;
+                }
+                (name, tail.to_string())
+            } else {
+                continue;
+            };
+            let after = after.trim_start();
+            let is_write = after.starts_with('=') && !after.starts_with("==");
+            out.push((key, is_write));
+        }
+    }
+
+    for (call, is_write) in [("SQE.getAnswer(", false), ("SQE.setAnswer(", true)] {
+        let mut rest = script;
+        while let Some(pos) = rest.find(call) {
+            rest = &rest[pos + call.len()..];
+            if let Some((k, _)) = string_literal(rest.trim_start()) {
+                out.push((k, is_write));
+            }
+        }
+    }
+
+    out
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// Parse a leading JS string literal ('...', "..." or `...` without interpolation).
+fn string_literal(s: &str) -> Option<(String, &str)> {
+    let quote = s.chars().next()?;
+    if !matches!(quote, '"' | '\'' | '`') {
+        return None;
+    }
+    let body = &s[1..];
+    let end = body.find(quote)?;
+    let lit = &body[..end];
+    if lit.contains('\\') || (quote == '`' && lit.contains("${")) {
+        return None;
+    }
+    Some((lit.to_string(), &body[end + 1..]))
+}
+
+/// Collect the values of `attr="..."` occurrences in an HTML fragment.
+pub(crate) fn attr_values(html: &str, attr: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut rest = html;
+    while let Some(pos) = rest.find(attr) {
+        rest = &rest[pos + attr.len()..];
+        if let Some(v) = rest.trim_start().strip_prefix('=')
+            && let Some((lit, _)) = string_literal(v.trim_start())
+        {
+            out.push(lit);
+        }
+    }
+    out
+}
+
+/// All findings with their error and warning counts, the JSON form of a lint run.
+pub fn to_json(findings: &[Finding]) -> Value {
+    json!({
+        "findings": findings.iter().map(Finding::to_json).collect::<Vec<_>>(),
+        "errors": findings.iter().filter(|f| f.level == Level::Error).count(),
+        "warnings": findings.iter().filter(|f| f.level == Level::Warning).count(),
+    })
+}
+
+/// Render findings either as human-readable lines or as a JSON document.
+pub fn render(findings: &[Finding], json_output: bool) -> String {
+    if json_output {
+        return serde_json::to_string_pretty(&to_json(findings)).unwrap_or_default();
+    }
+    let errors = findings.iter().filter(|f| f.level == Level::Error).count();
+    let warnings = findings.iter().filter(|f| f.level == Level::Warning).count();
+    let mut out = String::new();
+    for f in findings {
+        out.push_str(&f.to_human());
+        out.push('\n');
+    }
+    out.push_str(&format!("{} error(s), {} warning(s)", errors, warnings));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::{Bank, Choose, Draw, Js, Quiz};
+
+    fn page(title: &str, content: Vec<Question>) -> Entry {
+        Entry::Page {
+            title: title.to_string(),
+            time_limit: None,
+            content,
+        }
+    }
+
+    fn rules(findings: &[Finding]) -> Vec<&'static str> {
+        findings.iter().map(|f| f.rule).collect()
+    }
+
+    #[test]
+    fn flags_questionnaire_problems() {
+        let ast = vec![
+            Entry::Quiz(Quiz::default()),
+            page(
+                "One",
+                vec![
+                    Question::Choose(Box::new(Choose::parse("Q?\nYes >> 1\nNo >> 1", Some("q1".into())))),
+                    Question::Choose(Box::new(Choose::parse("Again?\nYes >> 1", Some("q1".into())))),
+                    Question::Choose(Box::new(Choose::parse("", None))),
+                ],
+            ),
+            page("Empty", vec![]),
+            page(
+                "Script",
+                vec![Question::Js(Js::parse(
+                    "const a = window.SQE_ANSWERS || {};\nif (a[\"q1\"] && a.q9) {}\nSQE_ANSWERS['q2'] = 1;\nSQE.getAnswer('q2');",
+                ))],
+            ),
+        ];
+        let found = lint(&ast, &LintConfig::default());
+        let r = rules(&found);
+        assert!(r.contains(&"duplicate-option-value"));
+        assert!(r.contains(&"duplicate-id"));
+        assert!(r.contains(&"missing-id"));
+        assert!(r.contains(&"empty-question"));
+        assert!(r.contains(&"empty-page"));
+        assert!(r.contains(&"ungraded-choice"));
+        let undefined: Vec<&str> = found
+            .iter()
+            .filter(|f| f.rule == "undefined-answer-key")
+            .map(|f| f.message.as_str())
+            .collect();
+        assert_eq!(undefined.len(), 1);
+        assert!(undefined[0].contains("`q9`"));
+    }
+
+    #[test]
+    fn warns_about_draws_in_quiz_mode() {
+        let bank = Bank::parse("exam", "choice a {\nA?\nYes >> 1 .correct\n}\nchoice b {\nB?\nYes >> 1 .correct\n}").unwrap();
+        let mut draw = Draw::parse("1 from exam").unwrap();
+        draw.resolve(&bank).unwrap();
+        let mut ast = vec![Entry::Bank(bank), page("Exam", vec![Question::Draw(Box::new(draw))])];
+        assert!(!rules(&lint(&ast, &LintConfig::default())).contains(&"quiz-draw"));
+        ast.insert(0, Entry::Quiz(Quiz::default()));
+        let found = lint(&ast, &LintConfig::default());
+        assert_eq!(rules(&found), vec!["quiz-draw"]);
+        assert!(found[0].message.contains("all 2 matching questions"));
+    }
+
+    #[test]
+    fn rules_are_configurable() {
+        let ast = vec![page(
+            "One",
+            vec![Question::Choose(Box::new(Choose::parse("A rather long question?\nYes\nNo", None)))],
+        )];
+        let mut config = LintConfig::default();
+        config.set_level("missing-id", Level::Allow).unwrap();
+        config.set_level("long-question", Level::Error).unwrap();
+        config.max_question_length = 10;
+        let found = lint(&ast, &config);
+        assert_eq!(rules(&found), vec!["long-question"]);
+        assert_eq!(found[0].level, Level::Error);
+        assert!(config.set_level("no-such-rule", Level::Error).is_err());
+    }
+
+    #[test]
+    fn renders_json_document() {
+        let ast = vec![page(
+            "One",
+            vec![Question::Choose(Box::new(Choose::parse("A rather long question?\nYes\nNo", None)))],
+        )];
+        let found = lint(&ast, &LintConfig::default());
+        let doc: Value = serde_json::from_str(&render(&found, true)).unwrap();
+        assert_eq!(doc["findings"][0]["rule"], "missing-id");
+        assert_eq!(doc["findings"][0]["page"], 1);
+        assert_eq!((doc["errors"].as_u64(), doc["warnings"].as_u64()), (Some(0), Some(found.len() as u64)));
+    }
+}
@@ -1,12 +1,30 @@
 // === src/main.rs ===
 
+mod codebook;
+mod commands;
 mod convert;
+mod export;
+mod expr;
+mod formatter;
+mod grading;
 mod items;
+mod lint;
+mod messages;
+mod norms;
+mod psychometrics;
+mod report;
+mod responses;
+mod schema;
+mod scoring;
+mod server;
+mod table;
 mod transcompiler;
+mod watch;
 
-use std::path::Path;
-use std::process;
-use clap::Parser;
+use clap::{Parser, Subcommand};
+
+use crate::commands::{build, check, export as export_cmd, fmt, lint as lint_cmd, merge, new, report as report_cmd, score, serve, stats, validate_answers};
+use crate::messages::{MessageFormat, Reporter};
 
 #[derive(Parser, Debug)]
 #[command(
@@ -16,56 +34,70 @@ use clap::Parser;
     after_help = "We value transparency and open-source collaboration. With that freedom comes responsibility: please test our tools in safe environments before production use. This product is provided as-is, without warranty of any kind."
 )]
 struct Args {
-    /// Input .sqe file to compile
-    #[arg(long, value_name = "FILE")]
-    input: String,
+    #[command(subcommand)]
+    command: Command,
 
-    /// Output directory (defaults to ./out)
-    #[arg(long, value_name = "DIR", default_value = "out")]
-    output: String,
-}
+    /// Only print errors and command results
+    #[arg(long, short, global = true, conflicts_with = "verbose")]
+    quiet: bool,
 
-fn main() -> std::io::Result<()> {
-    let args = Args::parse();
+    /// Print extra detail such as build metadata
+    #[arg(long, short, global = true)]
+    verbose: bool,
 
-    // Basic validations with helpful messages
-    let input_path = Path::new(&args.input);
-    if !input_path.exists() {
-        eprintln!("Input file does not exist: {}", input_path.display());
-        eprintln!("Run with --help for usage information.");
-        process::exit(2);
-    }
-    if !input_path.is_file() {
-        eprintln!("Input path is not a file: {}", input_path.display());
-        process::exit(2);
-    }
+    /// Format of status messages and diagnostics
+    #[arg(long, global = true, value_enum, default_value_t = MessageFormat::Human)]
+    message_format: MessageFormat,
+}
 
-    let out_dir = &args.output;
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Compile a .sqe file into HTML pages
+    Build(build::BuildArgs),
+    /// Compile and lint without writing output
+    Check(check::CheckArgs),
+    /// Format .sqe source files
+    Fmt(fmt::FmtArgs),
+    /// Check a questionnaire for common authoring problems
+    Lint(lint_cmd::LintArgs),
+    /// Build and preview a questionnaire on a local web server
+    Serve(serve::ServeArgs),
+    /// Export the questionnaire structure as JSON, or a codebook
+    Export(export_cmd::ExportArgs),
+    /// Merge saved answers files into one wide CSV/TSV
+    Merge(merge::MergeArgs),
+    /// Create a new questionnaire from a template
+    New(new::NewArgs),
+    /// Write an HTML report over many saved responses
+    Report(report_cmd::ReportArgs),
+    /// Compute declared scale scores for saved answers
+    Score(score::ScoreArgs),
+    /// Reliability and item statistics for declared scales
+    Stats(stats::StatsArgs),
+    /// Check saved answers files against the questionnaire's answers schema
+    ValidateAnswers(validate_answers::ValidateAnswersArgs),
+}
 
-    // Compile and write pages
-    let ast = match transcompiler::compile(input_path.to_str().unwrap()) {
-        Ok(a) => a,
-        Err(e) => {
-            eprintln!("Failed to compile {}: {}", input_path.display(), e);
-            process::exit(1);
-        }
+fn main() -> std::io::Result<()> {
+    let args = Args::parse();
+    let out = Reporter {
+        quiet: args.quiet,
+        verbose: args.verbose,
+        format: args.message_format,
     };
-    println!("Parsed AST:\n{:#?}", ast);
 
-    if let Err(e) = convert::build_pages(&ast, out_dir) {
-        eprintln!("Failed to write output to {}: {}", out_dir, e);
-        process::exit(1);
+    match &args.command {
+        Command::Build(a) => build::run(a, &out),
+        Command::Check(a) => check::run(a, &out),
+        Command::Fmt(a) => fmt::run(a, &out),
+        Command::Lint(a) => lint_cmd::run(a, &out),
+        Command::Serve(a) => serve::run(a, &out),
+        Command::Export(a) => export_cmd::run(a, &out),
+        Command::Merge(a) => merge::run(a, &out),
+        Command::New(a) => new::run(a, &out),
+        Command::Report(a) => report_cmd::run(a, &out),
+        Command::Score(a) => score::run(a, &out),
+        Command::Stats(a) => stats::run(a, &out),
+        Command::ValidateAnswers(a) => validate_answers::run(a, &out),
     }
-    println!("Wrote HTML files to {} (open {}/index.html)", out_dir, out_dir);
- 
-    // Print package & build metadata embedded at compile time
-    println!("Version: {}", env!("CARGO_PKG_VERSION"));
-    println!("Git commit: {}", env!("GIT_COMMIT"));
-    println!("Build time (UTC): {}", env!("BUILD_TIME"));
- 
-    // Print a terminal QR code (uses helper in items::qrcode to avoid repetition)
-    let index_url = "https://eypozax.github.io/docs/SQE/";
-    items::qrcode::print_qrcode(index_url);
- 
-    Ok(())
 }
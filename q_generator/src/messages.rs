@@ -0,0 +1,67 @@
+// === src/messages.rs ===
+
+use serde_json::{Value, json};
+
+/// How status messages and diagnostics are printed.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum MessageFormat {
+    #[default]
+    Human,
+    Json,
+}
+
+/// Central place for CLI output so `--quiet`, `--verbose` and `--message-format json`
+/// behave the same across all subcommands.
+///
+/// In JSON mode every message is one JSON object per line on stdout, which keeps the
+/// output easy to consume from CI scripts.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Reporter {
+    pub quiet: bool,
+    pub verbose: bool,
+    pub format: MessageFormat,
+}
+
+impl Reporter {
+    pub fn is_json(&self) -> bool {
+        self.format == MessageFormat::Json
+    }
+
+    /// Regular progress message; suppressed by `--quiet`.
+    pub fn info(&self, msg: &str) {
+        if self.quiet {
+            return;
+        }
+        if self.is_json() {
+            self.emit(json!({ "level": "info", "message": msg }));
+        } else {
+            println!("{}", msg);
+        }
+    }
+
+    /// Extra detail shown only with `--verbose`.
+    pub fn detail(&self, msg: &str) {
+        if !self.verbose || self.quiet {
+            return;
+        }
+        if self.is_json() {
+            self.emit(json!({ "level": "debug", "message": msg }));
+        } else {
+            println!("{}", msg);
+        }
+    }
+
+    /// Errors are always printed (to stderr in human mode).
+    pub fn error(&self, msg: &str) {
+        if self.is_json() {
+            self.emit(json!({ "level": "error", "message": msg }));
+        } else {
+            eprintln!("{}", msg);
+        }
+    }
+
+    /// Print a structured JSON record on its own line.
+    pub fn emit(&self, value: Value) {
+        println!("{}", value);
+    }
+}
@@ -0,0 +1,191 @@
+// === src/norms.rs ===
+
+//! Norm tables that convert raw scale scores into percentiles, T-scores and
+//! interpretive bands, optionally stratified by other answers (age group, sex, ...).
+//!
+//! A norm table is a CSV file with one column per stratification key, a `raw` column
+//! and any of `percentile`, `t` and `band`:
+//!
+//! ```text
+//! age,sex,raw,percentile,t,band
+//! 18-29,m,0-3,10,37,low
+//! 18-29,m,4-8,50,50,average
+//! 18-29,*,9-12,90,63,high
+//! ```
+//!
+//! Cells match exactly, as an inclusive numeric range (`18-29`), or anything (`*` or
+//! empty). The first matching row wins.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde_json::{Value, json};
+
+/// Converted score for one raw score.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct NormResult {
+    pub percentile: Option<f64>,
+    pub t: Option<f64>,
+    pub band: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NormTable {
+    /// Answer keys the table is stratified by, matching CSV columns
+    pub by: Vec<String>,
+    /// Rows as cells in column order of `columns`
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// Split CSV text into rows of fields (RFC 4180 quoting).
+pub fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            (true, '"') => quoted = false,
+            (true, c) => field.push(c),
+            (false, '"') => quoted = true,
+            (false, ',') => row.push(std::mem::take(&mut field)),
+            (false, '\r') => {}
+            (false, '\n') => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            (false, c) => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows.retain(|r| !(r.len() == 1 && r[0].trim().is_empty()));
+    rows
+}
+
+/// Whether a table cell matches a value: wildcard, inclusive numeric range, or equal.
+fn cell_matches(cell: &str, value: &Value) -> bool {
+    let cell = cell.trim();
+    if cell.is_empty() || cell == "*" {
+        return true;
+    }
+    let number = match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse::<f64>().ok(),
+        _ => None,
+    };
+    if let Some(x) = number {
+        // a leading '-' is a negative number, not a range separator
+        if let Some((pos, _)) = cell.char_indices().skip(1).find(|(_, c)| *c == '-')
+            && let (Ok(lo), Ok(hi)) = (cell[..pos].trim().parse::<f64>(), cell[pos + 1..].trim().parse::<f64>())
+        {
+            return lo <= x && x <= hi;
+        }
+        if let Ok(c) = cell.parse::<f64>() {
+            return c == x;
+        }
+    }
+    match value {
+        Value::String(s) => s == cell,
+        Value::Bool(b) => cell == if *b { "true" } else { "false" },
+        _ => false,
+    }
+}
+
+impl NormTable {
+    /// Read a norm table. `path` is resolved against `base` (the questionnaire's directory).
+    pub fn load(path: &str, by: Vec<String>, base: &Path) -> io::Result<Self> {
+        let text = fs::read_to_string(base.join(path))
+            .map_err(|e| io::Error::new(e.kind(), format!("norm table {}: {}", path, e)))?;
+        let mut rows = parse_csv(&text).into_iter();
+        let columns: Vec<String> = rows.next().unwrap_or_default().iter().map(|c| c.trim().to_string()).collect();
+        for required in by.iter().chain(std::iter::once(&"raw".to_string())) {
+            if !columns.contains(required) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("norm table {} has no `{}` column", path, required),
+                ));
+            }
+        }
+        Ok(NormTable {
+            by,
+            columns,
+            rows: rows.collect(),
+        })
+    }
+
+    fn column(&self, name: &str) -> Option<usize> {
+        self.columns.iter().position(|c| c == name)
+    }
+
+    /// Look up a raw score; `stratum` gives the answer for each `by` key.
+    pub fn lookup(&self, raw: f64, stratum: impl Fn(&str) -> Option<Value>) -> Option<NormResult> {
+        let raw_col = self.column("raw")?;
+        let strata: Vec<(usize, Value)> = self
+            .by
+            .iter()
+            .map(|key| (self.column(key).unwrap_or(usize::MAX), stratum(key).unwrap_or(Value::Null)))
+            .collect();
+        let row = self.rows.iter().find(|row| {
+            let cell = |i: usize| row.get(i).map(String::as_str).unwrap_or("");
+            cell_matches(cell(raw_col), &json!(raw)) && strata.iter().all(|(i, v)| cell_matches(cell(*i), v))
+        })?;
+        let get = |name: &str| {
+            self.column(name)
+                .and_then(|i| row.get(i))
+                .map(|s| s.trim())
+                .filter(|s| !s.is_empty())
+        };
+        Some(NormResult {
+            percentile: get("percentile").and_then(|s| s.parse().ok()),
+            t: get("t").and_then(|s| s.parse().ok()),
+            band: get("band").map(str::to_string),
+        })
+    }
+
+    /// Table as embedded in the generated page for the runtime lookup.
+    pub fn to_json(&self) -> Value {
+        json!({ "by": self.by, "columns": self.columns, "rows": self.rows })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> NormTable {
+        let rows = parse_csv("age,sex,raw,percentile,t,band\n18-29,m,0-3,10,37,low\n18-29,m,4-8,50,50,average\n\"18-29\",*,9-12,90,63,\"high, very\"\n");
+        let mut rows = rows.into_iter();
+        NormTable {
+            by: vec!["age".to_string(), "sex".to_string()],
+            columns: rows.next().unwrap(),
+            rows: rows.collect(),
+        }
+    }
+
+    #[test]
+    fn looks_up_by_stratum_and_range() {
+        let t = table();
+        let stratum = |age: Value, sex: &'static str| move |k: &str| Some(if k == "age" { age.clone() } else { json!(sex) });
+
+        let r = t.lookup(5.0, stratum(json!(20), "m")).unwrap();
+        assert_eq!(r.percentile, Some(50.0));
+        assert_eq!(r.t, Some(50.0));
+        assert_eq!(r.band.as_deref(), Some("average"));
+
+        let high = t.lookup(10.0, stratum(json!("25"), "f")).unwrap();
+        assert_eq!(high.band.as_deref(), Some("high, very"));
+
+        assert!(t.lookup(5.0, stratum(json!(40), "m")).is_none());
+        assert!(t.lookup(5.0, stratum(json!(20), "f")).is_none());
+    }
+}
@@ -0,0 +1,285 @@
+// === src/psychometrics.rs ===
+
+//! Internal-consistency and item statistics for declared scales. Statistics use the
+//! reverse-keyed item values from [`Scorer`] and complete cases only (responses that
+//! answered every item of the scale).
+
+use serde_json::{Value, json};
+
+use crate::items::Scale;
+use crate::responses::Answers;
+use crate::scoring::Scorer;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemStats {
+    pub key: String,
+    pub reversed: bool,
+    pub mean: f64,
+    pub sd: f64,
+    /// Correlation of the item with the sum of the other items
+    pub corrected_item_total: Option<f64>,
+    /// Alpha of the scale without this item
+    pub alpha_if_deleted: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScaleStats {
+    pub name: String,
+    /// Responses that answered every item
+    pub n: usize,
+    pub mean: f64,
+    pub sd: f64,
+    pub alpha: Option<f64>,
+    pub omega: Option<f64>,
+    pub items: Vec<ItemStats>,
+}
+
+fn mean(xs: &[f64]) -> f64 {
+    xs.iter().sum::<f64>() / xs.len() as f64
+}
+
+/// Sample variance (n - 1 denominator).
+fn variance(xs: &[f64]) -> f64 {
+    if xs.len() < 2 {
+        return 0.0;
+    }
+    let m = mean(xs);
+    xs.iter().map(|x| (x - m).powi(2)).sum::<f64>() / (xs.len() - 1) as f64
+}
+
+fn correlation(xs: &[f64], ys: &[f64]) -> Option<f64> {
+    let (mx, my) = (mean(xs), mean(ys));
+    let cov: f64 = xs.iter().zip(ys).map(|(x, y)| (x - mx) * (y - my)).sum();
+    let sx: f64 = xs.iter().map(|x| (x - mx).powi(2)).sum::<f64>().sqrt();
+    let sy: f64 = ys.iter().map(|y| (y - my).powi(2)).sum::<f64>().sqrt();
+    if sx == 0.0 || sy == 0.0 { None } else { Some(cov / (sx * sy)) }
+}
+
+/// Cronbach's alpha of the columns of `rows` (one row per response).
+pub fn cronbach_alpha(rows: &[Vec<f64>]) -> Option<f64> {
+    let k = rows.first()?.len();
+    if k < 2 || rows.len() < 2 {
+        return None;
+    }
+    let item_var: f64 = (0..k).map(|j| variance(&column(rows, j))).sum();
+    let totals: Vec<f64> = rows.iter().map(|r| r.iter().sum()).collect();
+    let total_var = variance(&totals);
+    if total_var == 0.0 {
+        return None;
+    }
+    Some(k as f64 / (k as f64 - 1.0) * (1.0 - item_var / total_var))
+}
+
+fn column(rows: &[Vec<f64>], j: usize) -> Vec<f64> {
+    rows.iter().map(|r| r[j]).collect()
+}
+
+/// Dominant eigenpair of a symmetric matrix by power iteration.
+fn dominant_eigen(m: &[Vec<f64>]) -> (f64, Vec<f64>) {
+    let k = m.len();
+    let mut v = vec![1.0 / (k as f64).sqrt(); k];
+    let mut value = 0.0;
+    for _ in 0..500 {
+        let w: Vec<f64> = (0..k).map(|i| (0..k).map(|j| m[i][j] * v[j]).sum()).collect();
+        let norm = w.iter().map(|x| x * x).sum::<f64>().sqrt();
+        if norm == 0.0 {
+            return (0.0, v);
+        }
+        let next: Vec<f64> = w.iter().map(|x| x / norm).collect();
+        let delta: f64 = next.iter().zip(&v).map(|(a, b)| (a - b).abs()).sum();
+        v = next;
+        value = norm;
+        if delta < 1e-12 {
+            break;
+        }
+    }
+    (value, v)
+}
+
+/// McDonald's omega (total) from a one-factor model fitted by iterated principal axis
+/// factoring of the item correlation matrix.
+pub fn mcdonald_omega(rows: &[Vec<f64>]) -> Option<f64> {
+    let k = rows.first()?.len();
+    if k < 3 || rows.len() < 3 {
+        return None;
+    }
+    let cols: Vec<Vec<f64>> = (0..k).map(|j| column(rows, j)).collect();
+    let mut r = vec![vec![0.0; k]; k];
+    for i in 0..k {
+        for j in 0..k {
+            r[i][j] = if i == j { 1.0 } else { correlation(&cols[i], &cols[j])? };
+        }
+    }
+
+    // initial communalities: largest absolute correlation of each item
+    let mut h2: Vec<f64> = (0..k)
+        .map(|i| (0..k).filter(|j| *j != i).map(|j| r[i][j].abs()).fold(0.0, f64::max))
+        .collect();
+    let mut loadings = vec![0.0; k];
+    for _ in 0..200 {
+        let mut reduced = r.clone();
+        for i in 0..k {
+            reduced[i][i] = h2[i];
+        }
+        let (value, vector) = dominant_eigen(&reduced);
+        loadings = vector.iter().map(|v| v * value.max(0.0).sqrt()).collect();
+        let next: Vec<f64> = loadings.iter().map(|l| (l * l).min(0.995)).collect();
+        let delta: f64 = next.iter().zip(&h2).map(|(a, b)| (a - b).abs()).sum();
+        h2 = next;
+        if delta < 1e-9 {
+            break;
+        }
+    }
+    if loadings.iter().sum::<f64>() < 0.0 {
+        loadings.iter_mut().for_each(|l| *l = -*l);
+    }
+    let common = loadings.iter().sum::<f64>().powi(2);
+    let unique: f64 = loadings.iter().map(|l| 1.0 - (l * l).min(0.995)).sum();
+    Some(common / (common + unique))
+}
+
+/// Statistics of one scale over a set of responses.
+pub fn scale_stats(scorer: &Scorer, scale: &Scale, responses: &[&Answers]) -> ScaleStats {
+    let rows: Vec<Vec<f64>> = responses
+        .iter()
+        .filter_map(|a| scorer.item_values(scale, a).into_iter().collect::<Option<Vec<f64>>>())
+        .collect();
+    let totals: Vec<f64> = rows.iter().map(|r| r.iter().sum()).collect();
+    let k = scale.items.len();
+
+    let items = scale
+        .items
+        .iter()
+        .enumerate()
+        .map(|(j, item)| {
+            let values = column(&rows, j);
+            let rest: Vec<f64> = totals.iter().zip(&values).map(|(t, v)| t - v).collect();
+            let without: Vec<Vec<f64>> = rows
+                .iter()
+                .map(|r| r.iter().enumerate().filter(|(i, _)| *i != j).map(|(_, v)| *v).collect())
+                .collect();
+            ItemStats {
+                key: item.key.clone(),
+                reversed: item.reversed,
+                mean: if values.is_empty() { f64::NAN } else { mean(&values) },
+                sd: variance(&values).sqrt(),
+                corrected_item_total: if k > 1 && rows.len() > 2 { correlation(&values, &rest) } else { None },
+                alpha_if_deleted: cronbach_alpha(&without),
+            }
+        })
+        .collect();
+
+    ScaleStats {
+        name: scale.name.clone(),
+        n: rows.len(),
+        mean: if totals.is_empty() { f64::NAN } else { mean(&totals) },
+        sd: variance(&totals).sqrt(),
+        alpha: cronbach_alpha(&rows),
+        omega: mcdonald_omega(&rows),
+        items,
+    }
+}
+
+/// Statistics of every declared scale.
+pub fn all_scale_stats(scorer: &Scorer, responses: &[&Answers]) -> Vec<ScaleStats> {
+    scorer.scales.iter().map(|s| scale_stats(scorer, s, responses)).collect()
+}
+
+/// Rounded number for output; `null` for undefined statistics.
+fn stat(x: Option<f64>) -> Value {
+    match x {
+        Some(v) if v.is_finite() => json!((v * 10000.0).round() / 10000.0),
+        _ => Value::Null,
+    }
+}
+
+/// Text cell for a statistic (`–` when undefined).
+pub fn fmt_stat(x: Option<f64>) -> String {
+    match x {
+        Some(v) if v.is_finite() => format!("{:.3}", v),
+        _ => "–".to_string(),
+    }
+}
+
+impl ScaleStats {
+    /// Plain-text table: a scale summary line followed by one line per item.
+    pub fn to_text(&self) -> String {
+        let mut out = format!(
+            "{}: n={} mean={} sd={} alpha={} omega={}\n",
+            self.name,
+            self.n,
+            fmt_stat(Some(self.mean)),
+            fmt_stat(Some(self.sd)),
+            fmt_stat(self.alpha),
+            fmt_stat(self.omega)
+        );
+        out.push_str(&format!(
+            "  {:<16} {:>8} {:>8} {:>10} {:>14}\n",
+            "item", "mean", "sd", "r (corr.)", "alpha if del."
+        ));
+        for i in &self.items {
+            let key = if i.reversed { format!("-{}", i.key) } else { i.key.clone() };
+            out.push_str(&format!(
+                "  {:<16} {:>8} {:>8} {:>10} {:>14}\n",
+                key,
+                fmt_stat(Some(i.mean)),
+                fmt_stat(Some(i.sd)),
+                fmt_stat(i.corrected_item_total),
+                fmt_stat(i.alpha_if_deleted)
+            ));
+        }
+        out
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "scale": self.name,
+            "n": self.n,
+            "mean": stat(Some(self.mean)),
+            "sd": stat(Some(self.sd)),
+            "alpha": stat(self.alpha),
+            "omega": stat(self.omega),
+            "items": self.items.iter().map(|i| json!({
+                "key": i.key,
+                "reversed": i.reversed,
+                "mean": stat(Some(i.mean)),
+                "sd": stat(Some(i.sd)),
+                "corrected_item_total": stat(i.corrected_item_total),
+                "alpha_if_deleted": stat(i.alpha_if_deleted),
+            })).collect::<Vec<_>>(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data() -> Vec<Vec<f64>> {
+        vec![
+            vec![1.0, 2.0, 2.0],
+            vec![2.0, 2.0, 3.0],
+            vec![3.0, 4.0, 3.0],
+            vec![4.0, 4.0, 5.0],
+            vec![5.0, 5.0, 4.0],
+        ]
+    }
+
+    #[test]
+    fn alpha_matches_hand_computation() {
+        // item variances 2.5 + 1.8 + 1.3 = 5.6, total variance 14.7
+        let alpha = cronbach_alpha(&data()).unwrap();
+        assert!((alpha - 1.5 * (1.0 - 5.6 / 14.7)).abs() < 1e-12);
+        assert!(cronbach_alpha(&[vec![1.0, 2.0]]).is_none());
+    }
+
+    #[test]
+    fn omega_is_one_factor_reliability() {
+        let omega = mcdonald_omega(&data()).unwrap();
+        assert!(omega > 0.8 && omega < 1.0, "{}", omega);
+
+        // identical items load (almost) perfectly on one factor
+        let identical: Vec<Vec<f64>> = [1.0, 2.0, 3.0, 4.0].iter().map(|v| vec![*v; 3]).collect();
+        assert!(mcdonald_omega(&identical).unwrap() > 0.99);
+    }
+}
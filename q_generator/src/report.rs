@@ -0,0 +1,303 @@
+// === src/report.rs ===
+
+//! Static HTML report over many responses to one questionnaire: completion, answer
+//! frequencies per question and score distributions per scale. Charts are inline SVG
+//! so the report is a single self-contained file.
+
+use std::fmt::Write as _;
+
+use serde_json::Value;
+
+use crate::convert::write_head;
+use crate::export::{choice_items, document_lang, document_title};
+use crate::items::common::escape_html;
+use crate::items::missing::MissingKind;
+use crate::items::{Choose, MissingCode};
+use crate::psychometrics::{all_scale_stats, fmt_stat};
+use crate::responses::Answers;
+use crate::scoring::Scorer;
+use crate::transcompiler::Entry;
+
+const REPORT_CSS: &str = "\
+table.freq{border-collapse:collapse;margin:8px 0;min-width:60%}\
+table.freq th,table.freq td{border:1px solid #ddd;padding:4px 8px;text-align:left}\
+table.freq td.num{text-align:right;font-variant-numeric:tabular-nums}\
+.chart{display:block;margin:8px 0}\
+.muted{color:#666}\n";
+
+const BAR_COLOR: &str = "#4a7bd0";
+const CHART_WIDTH: f64 = 520.0;
+const LABEL_WIDTH: f64 = 160.0;
+const BAR_HEIGHT: f64 = 22.0;
+
+/// An answer that counts as given: not missing, not null, not an empty selection.
+pub fn is_answered(answer: Option<&Value>) -> bool {
+    match answer {
+        None | Some(Value::Null) => false,
+        Some(Value::String(s)) => !s.is_empty(),
+        Some(Value::Array(a)) => !a.is_empty(),
+        Some(_) => true,
+    }
+}
+
+fn percent(part: usize, whole: usize) -> String {
+    if whole == 0 { "–".to_string() } else { format!("{:.1}%", 100.0 * part as f64 / whole as f64) }
+}
+
+/// Horizontal bar chart; `bars` are `(label, count)`.
+pub fn bar_chart(bars: &[(String, usize)]) -> String {
+    let max = bars.iter().map(|(_, n)| *n).max().unwrap_or(0).max(1) as f64;
+    let height = BAR_HEIGHT * bars.len() as f64 + 4.0;
+    let plot = CHART_WIDTH - LABEL_WIDTH - 50.0;
+    let mut svg = format!(
+        "<svg class=\"chart\" xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" role=\"img\">",
+        w = CHART_WIDTH,
+        h = height
+    );
+    for (i, (label, n)) in bars.iter().enumerate() {
+        let y = 2.0 + BAR_HEIGHT * i as f64;
+        let width = plot * *n as f64 / max;
+        let short: String = if label.chars().count() > 24 {
+            label.chars().take(23).chain(std::iter::once('…')).collect()
+        } else {
+            label.clone()
+        };
+        let _ = write!(
+            svg,
+            "<text x=\"{lx}\" y=\"{ty}\" text-anchor=\"end\" font-size=\"12\">{label}</text>\
+             <rect x=\"{bx}\" y=\"{ry}\" width=\"{width:.1}\" height=\"{bh}\" fill=\"{color}\"><title>{full}: {n}</title></rect>\
+             <text x=\"{nx:.1}\" y=\"{ty}\" font-size=\"12\">{n}</text>",
+            lx = LABEL_WIDTH - 6.0,
+            ty = y + BAR_HEIGHT * 0.65,
+            label = escape_html(&short),
+            full = escape_html(label),
+            bx = LABEL_WIDTH,
+            ry = y + 2.0,
+            bh = BAR_HEIGHT - 4.0,
+            color = BAR_COLOR,
+            nx = LABEL_WIDTH + width + 4.0,
+        );
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+/// Histogram of scores as `(bin label, count)`: one bin per integer when the scores
+/// are whole numbers over a small range, otherwise ten equal-width bins.
+pub fn histogram(scores: &[f64]) -> Vec<(String, usize)> {
+    let (Some(lo), Some(hi)) = (
+        scores.iter().copied().reduce(f64::min),
+        scores.iter().copied().reduce(f64::max),
+    ) else {
+        return Vec::new();
+    };
+    if scores.iter().all(|s| s.fract() == 0.0) && hi - lo <= 30.0 {
+        let (lo, hi) = (lo as i64, hi as i64);
+        return (lo..=hi)
+            .map(|v| (v.to_string(), scores.iter().filter(|s| **s as i64 == v).count()))
+            .collect();
+    }
+    let bins = 10usize;
+    let width = ((hi - lo) / bins as f64).max(f64::EPSILON);
+    let mut counts = vec![0usize; bins];
+    for s in scores {
+        let idx = (((s - lo) / width) as usize).min(bins - 1);
+        counts[idx] += 1;
+    }
+    counts
+        .into_iter()
+        .enumerate()
+        .map(|(i, n)| {
+            let from = lo + width * i as f64;
+            (format!("{:.2}–{:.2}", from, from + width), n)
+        })
+        .collect()
+}
+
+/// Render the report for `responses` (answer maps) as a complete HTML document.
+pub fn render(ast: &[Entry], responses: &[&Answers]) -> String {
+    let title = document_title(ast);
+    let items = choice_items(ast);
+    let total = responses.len();
+
+    let mut head = Vec::new();
+    // writing to a Vec cannot fail
+    let _ = write_head(&mut head, &format!("Report: {}", title), &document_lang(ast), REPORT_CSS);
+    let mut html = String::from_utf8_lossy(&head).into_owned();
+
+    let _ = writeln!(html, "<h1>Report: {}</h1>", escape_html(&title));
+
+    // Completion: every required question answered (every question when none is required);
+    // choosing "don't know" or "refused" counts, codes written by the runtime do not.
+    let required: Vec<_> = items.iter().filter(|(_, _, c)| c.is_required()).collect();
+    let needed: Vec<_> = if required.is_empty() { items.iter().collect() } else { required };
+    let responded = |c: &Choose, a: Option<&Value>| {
+        is_answered(a) && a.and_then(|v| c.missing_kind(v)).is_none_or(MissingKind::is_option)
+    };
+    let complete = responses.iter().filter(|a| needed.iter().all(|(_, k, c)| responded(c, a.get(k)))).count();
+    // answered with a value, not a missing-value code
+    let given = |c: &Choose, a: Option<&Value>| is_answered(a) && a.and_then(|v| c.missing_kind(v)).is_none();
+    let _ = writeln!(html, "<h2>Summary</h2>");
+    let _ = writeln!(html, "<table class=\"freq\">");
+    let _ = writeln!(html, "<tr><th>Responses</th><td class=\"num\">{}</td></tr>", total);
+    let _ = writeln!(
+        html,
+        "<tr><th>Complete{}</th><td class=\"num\">{} ({})</td></tr>",
+        if needed.len() < items.len() { " (all required questions)" } else { "" },
+        complete,
+        percent(complete, total)
+    );
+    let _ = writeln!(html, "</table>");
+
+    let _ = writeln!(html, "<h2>Questions</h2>");
+    for (_, key, c) in &items {
+        let answered = responses.iter().filter(|a| given(c, a.get(key))).count();
+        // responses holding each missing-value code
+        let coded: Vec<(&MissingCode, usize)> = c
+            .missing
+            .iter()
+            .map(|m| {
+                let n = responses.iter().filter(|a| a.get(key).is_some_and(|v| m.value.as_ref().is_some_and(|mv| mv.matches(v)))).count();
+                (m, n)
+            })
+            .collect();
+        let unanswered = total - answered - coded.iter().map(|(_, n)| n).sum::<usize>();
+        let _ = writeln!(
+            html,
+            "<section class=\"question\"><h3>{}</h3><p class=\"muted\"><code>{}</code> · answered {} of {} ({}) · missing {}</p>",
+            escape_html(&c.question),
+            escape_html(key),
+            answered,
+            total,
+            percent(answered, total),
+            total - answered
+        );
+
+        let mut bars: Vec<(String, usize)> = Vec::new();
+        let mut other = 0usize;
+        for (label, value) in &c.options {
+            let n = responses
+                .iter()
+                .filter(|a| match a.get(key) {
+                    Some(Value::Array(vals)) => vals.iter().any(|v| value.matches(v)),
+                    Some(v) => value.matches(v),
+                    None => false,
+                })
+                .count();
+            bars.push((label.clone(), n));
+        }
+        for a in responses {
+            let vals: Vec<&Value> = match a.get(key) {
+                Some(Value::Array(vals)) => vals.iter().collect(),
+                Some(v) if given(c, Some(v)) => vec![v],
+                _ => Vec::new(),
+            };
+            if vals.iter().any(|v| !c.options.iter().any(|(_, o)| o.matches(v))) {
+                other += 1;
+            }
+        }
+
+        let _ = writeln!(html, "<table class=\"freq\"><tr><th>Option</th><th>Value</th><th>Count</th><th>%</th></tr>");
+        for ((label, n), (_, value)) in bars.iter().zip(&c.options) {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td><code>{}</code></td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+                escape_html(label),
+                escape_html(&value.to_string()),
+                n,
+                percent(*n, answered)
+            );
+        }
+        for (m, n) in &coded {
+            let _ = writeln!(
+                html,
+                "<tr><td class=\"muted\">{} <small>({})</small></td><td><code>{}</code></td><td class=\"num\">{}</td><td></td></tr>",
+                escape_html(m.label()),
+                m.kind.name(),
+                escape_html(&m.value.as_ref().map(|v| v.to_string()).unwrap_or_default()),
+                n
+            );
+        }
+        if other > 0 {
+            let _ = writeln!(html, "<tr><td colspan=\"2\" class=\"muted\">other values</td><td class=\"num\">{}</td><td></td></tr>", other);
+        }
+        let _ = writeln!(
+            html,
+            "<tr><td colspan=\"2\" class=\"muted\">missing</td><td class=\"num\">{}</td><td></td></tr></table>",
+            unanswered
+        );
+        html.push_str(&bar_chart(&bars));
+        html.push_str("</section>\n");
+    }
+
+    let scorer = Scorer::new(ast);
+    if !scorer.scales.is_empty() {
+        let _ = writeln!(html, "<h2>Scales</h2>");
+        for scale in &scorer.scales {
+            let scores: Vec<f64> = responses.iter().filter_map(|a| scorer.score_scale(scale, a).score).collect();
+            let _ = writeln!(
+                html,
+                "<section class=\"question\"><h3>{}</h3><p class=\"muted\">{} item(s) · scored for {} of {} response(s)</p>",
+                escape_html(&scale.name),
+                scale.items.len(),
+                scores.len(),
+                total
+            );
+            html.push_str(&bar_chart(&histogram(&scores)));
+            html.push_str("</section>\n");
+        }
+
+        let _ = writeln!(html, "<h2>Reliability</h2>");
+        let _ = writeln!(html, "<p class=\"muted\">Complete cases only; reverse-keyed items (marked −) are recoded.</p>");
+        for st in all_scale_stats(&scorer, responses) {
+            let _ = writeln!(
+                html,
+                "<section class=\"question\"><h3>{}</h3><p>n = {} · mean {} · SD {} · Cronbach's α {} · McDonald's ω {}</p>",
+                escape_html(&st.name),
+                st.n,
+                fmt_stat(Some(st.mean)),
+                fmt_stat(Some(st.sd)),
+                fmt_stat(st.alpha),
+                fmt_stat(st.omega)
+            );
+            let _ = writeln!(
+                html,
+                "<table class=\"freq\"><tr><th>Item</th><th>Mean</th><th>SD</th><th>Corrected item-total r</th><th>α if item deleted</th></tr>"
+            );
+            for i in &st.items {
+                let _ = writeln!(
+                    html,
+                    "<tr><td><code>{}{}</code></td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+                    if i.reversed { "−" } else { "" },
+                    escape_html(&i.key),
+                    fmt_stat(Some(i.mean)),
+                    fmt_stat(Some(i.sd)),
+                    fmt_stat(i.corrected_item_total),
+                    fmt_stat(i.alpha_if_deleted)
+                );
+            }
+            html.push_str("</table></section>\n");
+        }
+    }
+
+    html.push_str("</body></html>\n");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_bins() {
+        assert_eq!(
+            histogram(&[1.0, 3.0, 3.0]),
+            vec![("1".to_string(), 1), ("2".to_string(), 0), ("3".to_string(), 2)]
+        );
+        let wide = histogram(&[0.0, 0.5, 10.0]);
+        assert_eq!(wide.len(), 10);
+        assert_eq!(wide[0].1, 2);
+        assert_eq!(wide[9].1, 1);
+        assert!(histogram(&[]).is_empty());
+    }
+}
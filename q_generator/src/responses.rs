@@ -0,0 +1,160 @@
+// === src/responses.rs ===
+
+//! Append-only JSONL store for responses collected by `serve`. Each line is one
+//! self-contained record, so a crash can at worst lose the line being written and the
+//! file can be inspected or concatenated with standard tools.
+
+use std::collections::hash_map::RandomState;
+use std::fs::{self, OpenOptions};
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use chrono::{SecondsFormat, Utc};
+use serde_json::{Map, Value, json};
+
+/// Default file responses are appended to.
+pub const DEFAULT_STORE: &str = "responses.jsonl";
+
+#[derive(Debug)]
+pub struct ResponseStore {
+    path: PathBuf,
+    // serializes appends from concurrent connections
+    lock: Mutex<()>,
+}
+
+/// A fresh, practically unique response id: 16 hex digits mixing the current time,
+/// a process-wide counter and per-process random state.
+pub fn new_response_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let mut h = RandomState::new().build_hasher();
+    h.write_i64(Utc::now().timestamp_nanos_opt().unwrap_or_default());
+    h.write_u64(COUNTER.fetch_add(1, Ordering::SeqCst));
+    format!("{:016x}", h.finish())
+}
+
+impl ResponseStore {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        ResponseStore {
+            path: path.as_ref().to_path_buf(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append one submitted payload, returning the stored record (id, timestamp, response).
+    pub fn append(&self, response: Value) -> io::Result<Value> {
+        let record = json!({
+            "id": new_response_id(),
+            "received_at": Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            "response": response,
+        });
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(parent) = self.path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", record)?;
+        Ok(record)
+    }
+}
+
+/// Answer key to answer value, as in `SQE_ANSWERS`.
+pub type Answers = Map<String, Value>;
+
+/// Split a saved response into its metadata and answers. Accepts the three shapes
+/// responses come in: a bare answers map (older `answers.json` files), a record with
+/// `meta` and `answers` (downloads and submit payloads), and a store line from
+/// [`ResponseStore`] that wraps such a record in `response`.
+pub fn split_response(record: &Value) -> Option<(Map<String, Value>, &Answers)> {
+    let obj = record.as_object()?;
+    if let Some(inner) = obj.get("response")
+        && inner.is_object()
+    {
+        let (mut meta, answers) = split_response(inner)?;
+        for field in ["id", "received_at"] {
+            if let Some(v) = obj.get(field) {
+                meta.insert(field.to_string(), v.clone());
+            }
+        }
+        return Some((meta, answers));
+    }
+    match obj.get("answers") {
+        Some(Value::Object(answers)) => {
+            let meta = obj.get("meta").and_then(Value::as_object).cloned().unwrap_or_default();
+            Some((meta, answers))
+        }
+        _ => Some((Map::new(), obj)),
+    }
+}
+
+/// Read saved responses from `.json` files, `.jsonl` stores, or directories of those
+/// (not recursive, sorted by name). Each response is returned with a label naming
+/// where it came from (`file` or `file:line`).
+pub fn load_responses(path: &Path) -> io::Result<Vec<(String, Value)>> {
+    let invalid = |what: String, e: serde_json::Error| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", what, e));
+
+    if path.is_dir() {
+        let mut files: Vec<PathBuf> = fs::read_dir(path)?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.is_file() && matches!(p.extension().and_then(|e| e.to_str()), Some("json" | "jsonl")))
+            .collect();
+        files.sort();
+        let mut all = Vec::new();
+        for file in files {
+            all.extend(load_responses(&file)?);
+        }
+        return Ok(all);
+    }
+
+    let text = fs::read_to_string(path)?;
+    let name = path.display().to_string();
+    if path.extension().and_then(|e| e.to_str()) == Some("jsonl") {
+        let mut all = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let what = format!("{}:{}", name, i + 1);
+            let value = serde_json::from_str(line).map_err(|e| invalid(what.clone(), e))?;
+            all.push((what, value));
+        }
+        Ok(all)
+    } else {
+        let value = serde_json::from_str(&text).map_err(|e| invalid(name.clone(), e))?;
+        Ok(vec![(name, value)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_all_response_shapes() {
+        let bare = json!({ "q1": 1 });
+        let (meta, answers) = split_response(&bare).unwrap();
+        assert!(meta.is_empty());
+        assert_eq!(answers["q1"], 1);
+
+        let wrapped = json!({ "meta": { "hash": "abc" }, "answers": { "q1": 2 }, "labels": {} });
+        let (meta, answers) = split_response(&wrapped).unwrap();
+        assert_eq!(meta["hash"], "abc");
+        assert_eq!(answers["q1"], 2);
+
+        let stored = json!({ "id": "r1", "received_at": "t", "response": wrapped });
+        let (meta, answers) = split_response(&stored).unwrap();
+        assert_eq!(meta["id"], "r1");
+        assert_eq!(meta["hash"], "abc");
+        assert_eq!(answers["q1"], 2);
+
+        assert!(split_response(&json!([1, 2])).is_none());
+    }
+}
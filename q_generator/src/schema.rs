@@ -0,0 +1,232 @@
+// === src/schema.rs ===
+
+//! JSON Schema for the `answers.json` record of one questionnaire, and a validator for
+//! the subset of JSON Schema the generated schemas use (`type`, `enum`, `properties`,
+//! `required`, `additionalProperties`, `items`, `minItems`, `uniqueItems`).
+
+use serde_json::{Map, Value, json};
+
+use crate::export::{choice_items, content_hash, document_title, draws, other_fields};
+use crate::transcompiler::Entry;
+
+/// File name of the schema written next to `index.html`.
+pub const SCHEMA_FILE: &str = "answers.schema.json";
+
+/// Schema of the saved answers record (`{meta, answers, labels}`). Unanswered
+/// questions are stored as `null` (or `[]` for multiple choice), so only required
+/// questions without a `.show if` condition reject those. Missing-value codes are
+/// allowed for every question that declares them (for multiple choice, in place of
+/// the list).
+pub fn answers_schema(ast: &[Entry]) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    let draws = draws(ast);
+    for (_, key, c) in choice_items(ast) {
+        let values: Vec<Value> = c.options.iter().map(|(_, v)| v.to_json()).collect();
+        let codes: Vec<Value> = c.missing.iter().filter_map(|m| m.value.as_ref().map(|v| v.to_json())).collect();
+        // a question hidden by its `.show if` condition or not picked by its draw is
+        // stored unanswered
+        let drawn = draws.iter().any(|(_, d)| d.item_keys().contains(&key.as_str()));
+        let must_answer = c.is_required() && c.show_if.is_none() && !drawn;
+        let mut prop = if c.is_multiple() {
+            let mut p = json!({
+                "type": "array",
+                "items": { "enum": values },
+                "uniqueItems": true,
+            });
+            if must_answer {
+                p["minItems"] = json!(1);
+            }
+            if codes.is_empty() { p } else { json!({ "anyOf": [p, { "enum": codes }] }) }
+        } else {
+            let mut allowed = values;
+            allowed.extend(codes);
+            if !must_answer {
+                allowed.push(Value::Null);
+            }
+            json!({ "enum": allowed })
+        };
+        prop["description"] = json!(c.question);
+        if must_answer {
+            required.push(json!(key));
+        }
+        properties.insert(key, prop);
+    }
+    // the answer keys each draw picked
+    for (_, d) in &draws {
+        let prop = json!({
+            "type": "array",
+            "items": { "enum": d.item_keys() },
+            "uniqueItems": true,
+            "description": format!("questions drawn from bank {}", d.bank),
+        });
+        properties.insert(d.key.clone(), prop);
+    }
+    // "other" text fields are empty unless their option is chosen
+    for (_, label, other) in other_fields(ast) {
+        properties.insert(other, json!({ "type": ["string", "null"], "description": format!("{} (text)", label) }));
+    }
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": format!("Answers: {}", document_title(ast)),
+        "type": "object",
+        "required": ["answers"],
+        "properties": {
+            "meta": {
+                "type": "object",
+                "properties": {
+                    "title": { "type": "string" },
+                    "hash": { "type": "string", "description": format!("questionnaire version; this schema: {}", content_hash(ast)) },
+                    "lang": { "type": "string" },
+                    "generator": { "type": "object" },
+                    "started_at": { "type": "string", "format": "date-time" },
+                    "finished_at": { "type": "string", "format": "date-time" },
+                    "seed": { "type": "integer" },
+                    "paradata": { "type": "object" },
+                    "url": { "type": "string" },
+                    "user_agent": { "type": "string" },
+                },
+            },
+            "answers": {
+                "type": "object",
+                "properties": properties,
+                "required": required,
+                // scripts may store extra keys through SQE.setAnswer
+                "additionalProperties": true,
+            },
+            "labels": { "type": "object" },
+            // the page's own grade in `mode quiz`; `sqe-core score` recomputes it
+            "quiz": { "type": "object" },
+        },
+    })
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn has_type(value: &Value, ty: &str) -> bool {
+    let actual = type_name(value);
+    actual == ty || (ty == "number" && actual == "integer")
+}
+
+/// JSON values are equal for `enum` purposes when numbers compare equal (`1` and `1.0`).
+fn same_value(a: &Value, b: &Value) -> bool {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(x), Some(y)) => x == y,
+        _ => a == b,
+    }
+}
+
+/// Validate `value` against `schema`, returning one message per violation, prefixed
+/// with the JSON pointer of the offending value.
+pub fn validate(schema: &Value, value: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    check(schema, value, "", &mut errors);
+    errors
+}
+
+fn check(schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+    let at = if path.is_empty() { "/" } else { path };
+
+    match schema.get("type") {
+        Some(Value::String(ty)) if !has_type(value, ty) => {
+            errors.push(format!("{}: expected {}, found {}", at, ty, type_name(value)));
+            return;
+        }
+        Some(Value::Array(types)) if !types.iter().filter_map(Value::as_str).any(|t| has_type(value, t)) => {
+            errors.push(format!("{}: unexpected type {}", at, type_name(value)));
+            return;
+        }
+        _ => {}
+    }
+
+    if let Some(Value::Array(allowed)) = schema.get("enum")
+        && !allowed.iter().any(|a| same_value(a, value))
+    {
+        let list: Vec<String> = allowed.iter().map(Value::to_string).collect();
+        errors.push(format!("{}: {} is not one of {}", at, value, list.join(", ")));
+    }
+
+    if let Value::Object(obj) = value {
+        let props = schema.get("properties").and_then(Value::as_object);
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for key in required.iter().filter_map(Value::as_str) {
+                if !obj.contains_key(key) {
+                    errors.push(format!("{}: missing required property `{}`", at, key));
+                }
+            }
+        }
+        for (key, v) in obj {
+            let child = format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1"));
+            match props.and_then(|p| p.get(key)) {
+                Some(sub) => check(sub, v, &child, errors),
+                None => {
+                    if schema.get("additionalProperties") == Some(&Value::Bool(false)) {
+                        errors.push(format!("{}: unexpected property", child));
+                    }
+                }
+            }
+        }
+    }
+
+    if let Value::Array(items) = value {
+        if let Some(min) = schema.get("minItems").and_then(Value::as_u64)
+            && (items.len() as u64) < min
+        {
+            errors.push(format!("{}: expected at least {} item(s), found {}", at, min, items.len()));
+        }
+        if schema.get("uniqueItems") == Some(&Value::Bool(true)) {
+            for (i, item) in items.iter().enumerate() {
+                if items[..i].iter().any(|prev| same_value(prev, item)) {
+                    errors.push(format!("{}/{}: duplicate item {}", path, i, item));
+                }
+            }
+        }
+        if let Some(item_schema) = schema.get("items") {
+            for (i, item) in items.iter().enumerate() {
+                check(item_schema, item, &format!("{}/{}", path, i), errors);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::Choose;
+    use crate::transcompiler::Question;
+
+    #[test]
+    fn validates_answers_against_questionnaire() {
+        let ast = vec![Entry::Page {
+            title: "P".to_string(),
+            time_limit: None,
+            content: vec![
+                Question::Choose(Box::new(Choose::parse("Q1\nYes >> 1\nNo >> 0\n.addons [\nrequired\n]", Some("q1".into())))),
+                Question::Choose(Box::new(Choose::parse("Q2\nA >> a\nB >> b\n.addons [\nmultiple\n]", Some("q2".into())))),
+            ],
+        }];
+        let schema = answers_schema(&ast);
+
+        let ok = json!({ "meta": { "seed": 3 }, "answers": { "q1": 1, "q2": ["a", "b"], "extra": "x" } });
+        assert!(validate(&schema, &ok).is_empty(), "{:?}", validate(&schema, &ok));
+
+        let bad = json!({ "answers": { "q1": null, "q2": ["a", "c", "a"] } });
+        let errors = validate(&schema, &bad);
+        assert_eq!(errors.len(), 3, "{:?}", errors);
+        assert!(errors[0].starts_with("/answers/q1: null is not one of"));
+
+        let missing = validate(&schema, &json!({ "answers": {} }));
+        assert_eq!(missing, vec!["/answers: missing required property `q1`"]);
+    }
+}
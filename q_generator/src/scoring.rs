@@ -0,0 +1,265 @@
+// === src/scoring.rs ===
+
+//! Scale scoring for collected answers. Item values are the numeric option values;
+//! reverse-keyed items are mirrored within the item's own value range
+//! (`min + max - value`), so a 1..4 item scores 4 for 1 and a 0/1 item scores 1 for 0.
+//! Missing-value codes (`missing refused >> -99`) count as unanswered.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use serde_json::{Value, json};
+
+use crate::export::{choice_items, computed, input_keys, scales};
+use crate::items::computed::{Computed, evaluation_order, with_computed};
+use crate::items::missing::without_codes;
+use crate::items::scale::{Method, Missing};
+use crate::items::value::OptionValue;
+use crate::items::Scale;
+use crate::norms::NormResult;
+use crate::responses::Answers;
+use crate::transcompiler::Entry;
+
+/// Score of one scale for one response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScaleScore {
+    pub name: String,
+    /// `None` when fewer than `min-answered` items were answered
+    pub score: Option<f64>,
+    pub answered: usize,
+    pub items: usize,
+    /// Norm-referenced score, when the scale has a norm table with a matching row
+    pub norm: Option<NormResult>,
+}
+
+impl ScaleScore {
+    pub fn to_json(&self) -> Value {
+        let mut value = json!({
+            "score": self.score.map(number),
+            "answered": self.answered,
+            "items": self.items,
+        });
+        if let Some(norm) = &self.norm {
+            value["percentile"] = norm.percentile.map(number).unwrap_or(Value::Null);
+            value["t"] = norm.t.map(number).unwrap_or(Value::Null);
+            value["band"] = json!(norm.band);
+        }
+        value
+    }
+}
+
+/// JSON number for a score: integral values are written without a fraction.
+pub fn number(x: f64) -> Value {
+    if x.fract() == 0.0 && x.abs() < 1e15 { json!(x as i64) } else { json!(x) }
+}
+
+/// Numeric value of an answer: numbers and numeric strings; the values of a
+/// multiple-choice answer are added up. Missing and non-numeric answers, and a
+/// multiple-choice answer with nothing chosen (`[]`), give `None`.
+pub fn answer_number(answer: Option<&Value>) -> Option<f64> {
+    match answer? {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse::<f64>().ok().filter(|f| f.is_finite()),
+        Value::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+        Value::Array(items) if items.is_empty() => None,
+        Value::Array(items) => items.iter().map(|v| answer_number(Some(v))).sum(),
+        _ => None,
+    }
+}
+
+/// Scores the declared scales of one questionnaire.
+pub struct Scorer<'a> {
+    pub scales: Vec<&'a Scale>,
+    /// numeric value range (min, max) of every choice question's options
+    ranges: HashMap<String, (f64, f64)>,
+    /// computed variables in evaluation order, filled in when a response lacks them
+    computed: Vec<&'a Computed>,
+    /// missing-value codes of every choice question
+    codes: HashMap<String, Vec<OptionValue>>,
+}
+
+impl<'a> Scorer<'a> {
+    pub fn new(ast: &'a [Entry]) -> Self {
+        let mut ranges = HashMap::new();
+        let mut codes = HashMap::new();
+        for (_, key, c) in choice_items(ast) {
+            codes.insert(key.clone(), c.missing.iter().filter_map(|m| m.value.clone()).collect());
+            let values = c.options.iter().filter_map(|(_, v)| v.as_f64());
+            let range = values.fold(None, |acc: Option<(f64, f64)>, v| match acc {
+                Some((lo, hi)) => Some((lo.min(v), hi.max(v))),
+                None => Some((v, v)),
+            });
+            if let Some(r) = range {
+                ranges.insert(key, r);
+            }
+        }
+        let known = input_keys(ast).into_iter().collect();
+        let computed = evaluation_order(&computed(ast), &known).unwrap_or_default();
+        Scorer { scales: scales(ast), ranges, computed, codes }
+    }
+
+    /// Answers as scored: missing-value codes cleared, computed variables filled in.
+    fn prepare<'b>(&self, answers: &'b Answers) -> Cow<'b, Answers> {
+        match with_computed(&self.computed, &self.codes, answers) {
+            Cow::Borrowed(answers) => without_codes(&self.codes, answers),
+            Cow::Owned(filled) => Cow::Owned(without_codes(&self.codes, &filled).into_owned()),
+        }
+    }
+
+    /// Keyed value of every item of `scale`, in declaration order.
+    pub fn item_values(&self, scale: &Scale, answers: &Answers) -> Vec<Option<f64>> {
+        let answers = self.prepare(answers);
+        scale
+            .items
+            .iter()
+            .map(|item| {
+                let v = answer_number(answers.get(&item.key))?;
+                match (item.reversed, self.ranges.get(&item.key)) {
+                    (true, Some((lo, hi))) => Some(lo + hi - v),
+                    _ => Some(v),
+                }
+            })
+            .collect()
+    }
+
+    pub fn score_scale(&self, scale: &Scale, answers: &Answers) -> ScaleScore {
+        let values: Vec<f64> = self.item_values(scale, answers).into_iter().flatten().collect();
+        let answered = values.len();
+        let items = scale.items.len();
+        let score = if answered == 0 || answered < scale.min_answered {
+            None
+        } else {
+            let sum: f64 = values.iter().sum();
+            let mean = sum / answered as f64;
+            Some(match (scale.method, scale.missing) {
+                (Method::Mean, _) => mean,
+                (Method::Sum, Missing::Skip) => sum,
+                (Method::Sum, Missing::Prorate) => mean * items as f64,
+            })
+        };
+        let norm = match (score, &scale.norms) {
+            (Some(raw), Some(table)) => {
+                let answers = self.prepare(answers);
+                table.lookup(raw, |key| answers.get(key).cloned())
+            }
+            _ => None,
+        };
+        ScaleScore {
+            name: scale.name.clone(),
+            score,
+            answered,
+            items,
+            norm,
+        }
+    }
+
+    /// Scales as embedded in the generated page, for `SQE.scores()`: items with their
+    /// value range, scoring options and norm table.
+    pub fn runtime_json(&self) -> Value {
+        Value::Array(
+            self.scales
+                .iter()
+                .map(|s| {
+                    json!({
+                        "name": s.name,
+                        "items": s.items.iter().map(|i| json!({
+                            "key": i.key,
+                            "reversed": i.reversed,
+                            "range": self.ranges.get(&i.key).map(|(lo, hi)| [number(*lo), number(*hi)]),
+                        })).collect::<Vec<_>>(),
+                        "method": if s.method == Method::Mean { "mean" } else { "sum" },
+                        "missing": if s.missing == Missing::Prorate { "prorate" } else { "skip" },
+                        "min_answered": s.min_answered,
+                        "feedback": s.feedback,
+                        "norms": s.norms.as_ref().map(|n| n.to_json()),
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    /// Scores of all declared scales, in declaration order.
+    pub fn score(&self, answers: &Answers) -> Vec<ScaleScore> {
+        self.scales.iter().map(|s| self.score_scale(s, answers)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::Choose;
+    use crate::transcompiler::Question;
+
+    fn likert(id: &str) -> Question {
+        Question::Choose(Box::new(Choose::parse("Q\nNever >> 1\nSometimes >> 2\nOften >> 3\nAlways >> 4", Some(id.into()))))
+    }
+
+    #[test]
+    fn scores_with_reverse_keying_and_missing_values() {
+        let ast = vec![
+            Entry::Scale(Scale::parse("sum", "a -b c")),
+            Entry::Scale(Scale::parse("prorated", "a -b c\nmissing prorate")),
+            Entry::Scale(Scale::parse("mean", "a -b c\nmethod mean\nmin-answered 3")),
+            Entry::Page {
+                title: "P".to_string(),
+                time_limit: None,
+                content: vec![likert("a"), likert("b"), likert("c")],
+            },
+        ];
+        let scorer = Scorer::new(&ast);
+
+        let answers = json!({ "a": 4, "b": "1", "c": null });
+        let scores = scorer.score(answers.as_object().unwrap());
+        // b is reversed: 1 -> 4
+        assert_eq!(scores[0].score, Some(8.0));
+        assert_eq!(scores[0].answered, 2);
+        assert_eq!(scores[1].score, Some(12.0));
+        assert_eq!(scores[2].score, None);
+
+        let complete = json!({ "a": 1, "b": 2, "c": 4 });
+        let scores = scorer.score(complete.as_object().unwrap());
+        assert_eq!(scores[0].score, Some(8.0));
+        assert_eq!(scores[2].score.map(|s| (s * 100.0).round() / 100.0), Some(2.67));
+    }
+
+    #[test]
+    fn missing_codes_are_unanswered() {
+        let coded = |id: &str| {
+            let src = "Q\nNever >> 1\nSometimes >> 2\nOften >> 3\nAlways >> 4\n.missing refused >> -99";
+            Question::Choose(Box::new(Choose::parse(src, Some(id.into()))))
+        };
+        let ast = vec![
+            Entry::Scale(Scale::parse("sum", "a -b c")),
+            Entry::Let(Computed::parse("c_plus = coalesce(c, 0) + 1").unwrap()),
+            Entry::Page {
+                title: "P".to_string(),
+                time_limit: None,
+                content: vec![coded("a"), coded("b"), coded("c")],
+            },
+        ];
+        let scorer = Scorer::new(&ast);
+        // -99 is a missing-value code, not a value
+        let answers = json!({ "a": 4, "b": "1", "c": -99 });
+        let scores = scorer.score(answers.as_object().unwrap());
+        assert_eq!((scores[0].score, scores[0].answered), (Some(8.0), 2));
+        assert_eq!(scorer.prepare(answers.as_object().unwrap())["c_plus"], json!(1));
+    }
+
+    #[test]
+    fn empty_multiple_choice_is_unanswered() {
+        assert_eq!(answer_number(Some(&json!([]))), None);
+        assert_eq!(answer_number(Some(&json!([1, "2"]))), Some(3.0));
+
+        let ast = vec![
+            Entry::Scale(Scale::parse("sum", "a b\nmin-answered 2")),
+            Entry::Page {
+                title: "P".to_string(),
+                time_limit: None,
+                content: vec![likert("a"), likert("b")],
+            },
+        ];
+        let answers = json!({ "a": 2, "b": [] });
+        let scores = Scorer::new(&ast).score(answers.as_object().unwrap());
+        assert_eq!((scores[0].answered, scores[0].score), (1, None));
+    }
+}
@@ -0,0 +1,248 @@
+// === src/server.rs ===
+
+//! A tiny, dependency-free HTTP/1.1 server used by `serve` to preview compiled
+//! questionnaires locally. It only understands what the preview needs: GET/HEAD for
+//! static files from the output directory and small POST bodies for response
+//! collection. One thread per connection.
+
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::thread;
+
+#[derive(Debug)]
+pub struct Request {
+    pub method: String,
+    /// Path without the query string, e.g. `/index.html`
+    pub path: String,
+    pub body: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct Response {
+    pub status: u16,
+    pub content_type: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn new(status: u16, content_type: &str, body: impl Into<Vec<u8>>) -> Self {
+        Response {
+            status,
+            content_type: content_type.to_string(),
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    pub fn text(status: u16, body: &str) -> Self {
+        Response::new(status, "text/plain; charset=utf-8", body)
+    }
+
+    pub fn json(status: u16, value: &serde_json::Value) -> Self {
+        Response::new(status, "application/json", value.to_string())
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        _ => "Internal Server Error",
+    }
+}
+
+/// Largest request body accepted (answers files are small).
+const MAX_BODY: usize = 8 * 1024 * 1024;
+
+pub fn read_request(stream: &mut TcpStream) -> io::Result<Request> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("").to_string();
+    let target = parts.next().unwrap_or("/").to_string();
+    if method.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "empty request"));
+    }
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((k, v)) = line.split_once(':') {
+            headers.push((k.trim().to_string(), v.trim().to_string()));
+        }
+    }
+
+    let length = headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, v)| v.parse::<usize>().ok())
+        .unwrap_or(0);
+    if length > MAX_BODY {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "request body too large"));
+    }
+    let mut body = vec![0u8; length];
+    reader.read_exact(&mut body)?;
+
+    let path = target.split('?').next().unwrap_or("/").to_string();
+    Ok(Request { method, path, body })
+}
+
+pub fn write_response(stream: &mut TcpStream, resp: &Response, head_only: bool) -> io::Result<()> {
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n",
+        resp.status,
+        reason(resp.status),
+        resp.content_type,
+        resp.body.len()
+    );
+    for (k, v) in &resp.headers {
+        head.push_str(&format!("{}: {}\r\n", k, v));
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes())?;
+    if !head_only {
+        stream.write_all(&resp.body)?;
+    }
+    stream.flush()
+}
+
+fn content_type_for(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()).unwrap_or("") {
+        "html" | "htm" => "text/html; charset=utf-8",
+        "js" => "text/javascript; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "json" => "application/json",
+        "csv" => "text/csv; charset=utf-8",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Decode `%XX` escapes in a URL path; `None` when the result is not valid UTF-8.
+/// A `%` that does not start an escape is kept as is.
+fn percent_decode(url_path: &str) -> Option<String> {
+    let bytes = url_path.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .filter(|h| h.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|h| u8::from_str_radix(std::str::from_utf8(h).ok()?, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(b)) => {
+                out.push(b);
+                i += 3;
+            }
+            (b, _) => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+/// Resolve a URL path inside `root`, refusing anything that would escape it. The
+/// path is percent-decoded first, so an encoded `..` is refused as well.
+fn resolve(root: &Path, url_path: &str) -> Option<PathBuf> {
+    let decoded = percent_decode(url_path)?;
+    if decoded.contains('\0') {
+        return None;
+    }
+    let mut path = root.to_path_buf();
+    for comp in Path::new(decoded.trim_start_matches('/')).components() {
+        match comp {
+            Component::Normal(c) => path.push(c),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    if path.is_dir() {
+        path.push("index.html");
+    }
+    Some(path)
+}
+
+/// Serve a static file from `root` for a GET/HEAD request.
+pub fn static_file(root: &Path, req: &Request) -> Response {
+    if req.method != "GET" && req.method != "HEAD" {
+        return Response::text(405, "method not allowed");
+    }
+    match resolve(root, &req.path) {
+        Some(path) => match fs::read(&path) {
+            Ok(body) => Response::new(200, content_type_for(&path), body),
+            Err(_) => Response::text(404, "not found"),
+        },
+        None => Response::text(400, "bad path"),
+    }
+}
+
+/// Accept connections forever, answering each request with `handler` on its own thread.
+pub fn serve<F>(listener: TcpListener, handler: F) -> io::Result<()>
+where
+    F: Fn(&Request, &mut TcpStream) -> io::Result<Option<Response>> + Send + Sync + Clone + 'static,
+{
+    for stream in listener.incoming() {
+        let mut stream = match stream {
+            Ok(s) => s,
+            Err(_) => continue,
+        };
+        let handler = handler.clone();
+        thread::spawn(move || {
+            let req = match read_request(&mut stream) {
+                Ok(r) => r,
+                Err(_) => {
+                    let _ = write_response(&mut stream, &Response::text(400, "bad request"), false);
+                    return;
+                }
+            };
+            // A handler may take over the stream (returning None), e.g. for streaming responses.
+            match handler(&req, &mut stream) {
+                Ok(Some(resp)) => {
+                    let _ = write_response(&mut stream, &resp, req.method == "HEAD");
+                }
+                Ok(None) => {}
+                Err(_) => {
+                    let _ = write_response(&mut stream, &Response::text(500, "internal error"), false);
+                }
+            }
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_encoded_paths_inside_root() {
+        let root = Path::new("/srv/out");
+        assert_eq!(resolve(root, "/my%20page.html"), Some(root.join("my page.html")));
+        assert_eq!(resolve(root, "/caf%C3%A9.png"), Some(root.join("café.png")));
+        assert_eq!(resolve(root, "/100%.txt"), Some(root.join("100%.txt")));
+        assert_eq!(resolve(root, "/%+1.txt"), Some(root.join("%+1.txt")));
+        assert_eq!(resolve(root, "/%2e%2e/secret"), None);
+        assert_eq!(resolve(root, "/a/..%2F..%2Fsecret"), None);
+        assert_eq!(resolve(root, "/%FF"), None);
+    }
+}
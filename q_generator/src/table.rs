@@ -0,0 +1,78 @@
+// === src/table.rs ===
+
+//! Delimited text output (CSV / TSV) for answers. The generated runtime writes the same
+//! format in JS, so a downloaded one-row file and a merged file line up column for column.
+
+use serde_json::Value;
+
+/// Default separator for the values of multi-select answers within one cell.
+pub const DEFAULT_MULTI_SEPARATOR: &str = ";";
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum TableFormat {
+    #[default]
+    Csv,
+    Tsv,
+}
+
+impl TableFormat {
+    pub fn delimiter(self) -> char {
+        match self {
+            TableFormat::Csv => ',',
+            TableFormat::Tsv => '\t',
+        }
+    }
+}
+
+/// Quote a field when it contains the delimiter, a quote or a line break (RFC 4180);
+/// embedded quotes are doubled.
+pub fn quote_field(field: &str, delimiter: char) -> String {
+    if field.contains([delimiter, '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Text of one answer cell: empty for missing answers, multi-select values joined
+/// with `multi_separator`, other structured values as JSON.
+pub fn answer_cell(value: Option<&Value>, multi_separator: &str) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Array(items)) => items
+            .iter()
+            .map(|v| answer_cell(Some(v), multi_separator))
+            .collect::<Vec<_>>()
+            .join(multi_separator),
+        Some(v) => v.to_string(),
+    }
+}
+
+/// One delimited line (without the line terminator).
+pub fn row<S: AsRef<str>>(fields: &[S], delimiter: char) -> String {
+    fields
+        .iter()
+        .map(|f| quote_field(f.as_ref(), delimiter))
+        .collect::<Vec<_>>()
+        .join(&delimiter.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn quotes_and_joins_cells() {
+        let multi = json!(["a", "b,c", 3]);
+        let cells = vec![
+            answer_cell(Some(&json!(1)), ";"),
+            answer_cell(Some(&multi), ";"),
+            answer_cell(Some(&json!("say \"hi\"")), ";"),
+            answer_cell(None, ";"),
+        ];
+        assert_eq!(row(&cells, ','), "1,\"a;b,c;3\",\"say \"\"hi\"\"\",");
+        assert_eq!(row(&cells, '\t'), "1\ta;b,c;3\t\"say \"\"hi\"\"\"\t");
+    }
+}
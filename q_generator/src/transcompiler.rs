@@ -1,9 +1,11 @@
 use std::fs::File;
 use std::io::{self, BufRead};
-use std::path::Path;
+use std::path::{Path, PathBuf};
  
-use crate::items::{Choose, Insert, Html, Js, Css};
-use crate::items::qrcode::print_qrcode;
+use crate::items::{Bank, Choose, Computed, Draw, Insert, Html, Js, Css, MissingCode, Navigation, Quiz, Scale, Submit};
+use crate::items::timer::parse_time_limit;
+use crate::export::check_expressions;
+use crate::norms::NormTable;
 
 #[derive(Debug)]
 pub enum Entry {
@@ -12,23 +14,56 @@ pub enum Entry {
     },
     /// Document-level title (the big title for the whole questionnaire)
     DocTitle(String),
+    /// Document language (`lang de`), used for the page's `lang` attribute and exports
+    Lang(String),
+    /// Separator for multi-select answers in CSV/TSV output (`multi-separator "|"`)
+    MultiSeparator(String),
+    /// Document-level `scale` declaration (answer keys scored together)
+    Scale(Scale),
+    /// Computed variable (`let bmi = weight / (height / 100) ^ 2`)
+    Let(Computed),
+    /// Document-level missing-value code (`missing refused >> -99`)
+    Missing(MissingCode),
+    /// Document-level `mode quiz` (grade the choices with correct options)
+    Quiz(Quiz),
+    /// Pool of tagged questions for `draw` (`bank exam { choice ... }`)
+    Bank(Bank),
+    /// Document-level `progress`, `toc` or `navigation free` directive
+    Navigation(Navigation),
+    /// Document-level `submit` directive (where answers are sent on save)
+    Submit(Submit),
     Page {
         title: String,
+        /// Seconds the respondent has for the page (`timer 90s`)
+        time_limit: Option<u32>,
         content: Vec<Question>,
     },
 }
 
 #[derive(Debug)]
 pub enum Question {
-    Choose(Choose),
+    Choose(Box<Choose>),
+    /// Random pick from a bank (`draw 10 from exam where topic == "safety"`)
+    Draw(Box<Draw>),
     Insert(Insert),
     Html(Html),
     Js(Js),
     Css(Css),
 }
 
+impl Question {
+    /// The choice questions of the item: the choice itself, or the pool of a draw.
+    pub fn choices(&self) -> Vec<&Choose> {
+        match self {
+            Question::Choose(c) => vec![c.as_ref()],
+            Question::Draw(d) => d.items.iter().collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
 // --- new helper: reads a brace-delimited block while ignoring braces inside strings ---
-fn read_brace_block<I>(
+pub(crate) fn read_brace_block<I>(
     lines: &mut std::iter::Peekable<I>,
     first_after_open: &str,
 ) -> io::Result<String>
@@ -79,23 +114,15 @@ where
                         in_backtick = !in_backtick;
                     }
                 }
-                '{' => {
-                    if !in_single && !in_double && !in_backtick {
-                        depth += 1;
-                        out.push(ch);
-                    } else {
-                        out.push(ch);
-                    }
+                '{' if !in_single && !in_double && !in_backtick => {
+                    depth += 1;
+                    out.push(ch);
                 }
-                '}' => {
-                    if !in_single && !in_double && !in_backtick {
-                        depth -= 1;
-                        if depth == 0 {
-                            // we reached the matching closing brace — DONE (do NOT include this brace)
-                            return None; // signal done for caller to stop
-                        } else {
-                            out.push(ch);
-                        }
+                '}' if !in_single && !in_double && !in_backtick => {
+                    depth -= 1;
+                    if depth == 0 {
+                        // we reached the matching closing brace — DONE (do NOT include this brace)
+                        return None; // signal done for caller to stop
                     } else {
                         out.push(ch);
                     }
@@ -111,14 +138,12 @@ where
     };
 
     // process the remainder of the line after the opening brace first
-    if !first_after_open.is_empty() {
-        if process_chars(first_after_open).is_none() {
-            return Ok(out);
-        }
+    if !first_after_open.is_empty() && process_chars(first_after_open).is_none() {
+        return Ok(out);
     }
 
     // then continue reading subsequent lines until depth returns to 0 or EOF
-    while let Some(line_res) = lines.next() {
+    for line_res in lines.by_ref() {
         let line = line_res?;
         if process_chars(&line).is_none() {
             return Ok(out);
@@ -126,51 +151,27 @@ where
     }
 
     // If we exhausted input without closing, return an error
-    let index_url = "https://maibloom.github.io/docs";
-    print_qrcode(index_url);
     Err(io::Error::new(
         io::ErrorKind::UnexpectedEof,
         "unterminated brace block",
     ))
 }
 
-// --- old parse_block kept for compatibility when simple closing marker is desired ---
-// (You can keep it or remove it. It's still used nowhere after this change.)
-pub fn parse_block<I>(
-    lines: &mut std::iter::Peekable<I>,
-    closing_marker: &str,
-) -> io::Result<String>
-where
-    I: Iterator<Item = io::Result<String>>,
-{
-    let mut out = String::new();
-    while let Some(line_res) = lines.next() {
-        let line = line_res?;
-        if line.contains(closing_marker) {
-            if let Some(pos) = line.find(closing_marker) {
-                let before = &line[..pos];
-                if !before.trim().is_empty() {
-                    out.push_str(before);
-                    out.push('\n');
-                }
-            }
-            break;
-        } else {
-            out.push_str(&line);
-            out.push('\n');
-        }
-    }
-    Ok(out)
-}
-
 // ... then the compile() function follows but with updated block handling ...
 pub fn compile<P: AsRef<Path>>(path: P) -> io::Result<Vec<Entry>> {
+    compile_importing(path.as_ref(), &mut Vec::new())
+}
+
+/// [`compile`], with the files whose imports are being resolved (to stop import cycles).
+fn compile_importing(path: &Path, importing: &mut Vec<PathBuf>) -> io::Result<Vec<Entry>> {
     let file = File::open(path)?;
     let reader = io::BufReader::new(file);
     let mut lines_iter = reader.lines().peekable();
 
     let mut ast: Vec<Entry> = Vec::new();
     let mut current_page: Option<(String, Vec<Question>)> = None;
+    // `timer` of the current page
+    let mut time_limit: Option<u32> = None;
 
     while let Some(line_res) = lines_iter.next() {
         let raw = line_res?;
@@ -182,9 +183,9 @@ pub fn compile<P: AsRef<Path>>(path: P) -> io::Result<Vec<Entry>> {
  
         // Support a document-level `title` directive. This sets the questionnaire-wide title
         // and does NOT create or modify the current page. Use @p for per-page titles.
-        if line.starts_with("title") {
+        if let Some(rest) = line.strip_prefix("title") {
             // use the trimmed `line` (not raw) so leading whitespace is handled consistently
-            let rest = line["title".len()..].trim();
+            let rest = rest.trim();
             let title = if rest.starts_with('"') {
                 rest.split('"').nth(1).unwrap_or("untitled").to_string()
             } else if !rest.is_empty() {
@@ -197,11 +198,11 @@ pub fn compile<P: AsRef<Path>>(path: P) -> io::Result<Vec<Entry>> {
             continue;
         }
  
-        if line.starts_with("@p") {
+        if let Some(rest) = line.strip_prefix("@p") {
             // Support both quoted and unquoted page titles:
             //   @p "Page One"
             //   @p Page One
-            let rest = line["@p".len()..].trim();
+            let rest = rest.trim();
             let page_title = if rest.starts_with('"') {
                 rest.split('"').nth(1).unwrap_or("untitled").to_string()
             } else if !rest.is_empty() {
@@ -219,7 +220,7 @@ pub fn compile<P: AsRef<Path>>(path: P) -> io::Result<Vec<Entry>> {
                 } else {
                     // Close the current page and start a new one with the provided title.
                     if let Some((t, c)) = current_page.take() {
-                        ast.push(Entry::Page { title: t, content: c });
+                        ast.push(Entry::Page { title: t, time_limit: time_limit.take(), content: c });
                     }
                     current_page = Some((page_title, Vec::new()));
                     continue;
@@ -231,6 +232,144 @@ pub fn compile<P: AsRef<Path>>(path: P) -> io::Result<Vec<Entry>> {
             }
         }
 
+        if let Some(rest) = line.strip_prefix("lang")
+            && (rest.is_empty() || rest.starts_with(char::is_whitespace))
+        {
+            let lang = rest.trim().trim_matches('"');
+            if !lang.is_empty() {
+                ast.push(Entry::Lang(lang.to_string()));
+            }
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("multi-separator") {
+            let rest = rest.trim();
+            let sep = if rest.starts_with('"') { rest.split('"').nth(1).unwrap_or("") } else { rest };
+            if !sep.is_empty() {
+                ast.push(Entry::MultiSeparator(sep.to_string()));
+            }
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("scale")
+            && rest.starts_with(char::is_whitespace)
+        {
+            // `scale name { items }`; the block may also start on the next line
+            let (name, block) = match rest.find('{') {
+                Some(open_pos) => (&rest[..open_pos], read_brace_block(&mut lines_iter, &rest[open_pos + 1..])?),
+                None => (rest, read_brace_block(&mut lines_iter, "")?),
+            };
+            let mut scale = Scale::parse(name, &block);
+            if let Some((file, by)) = &scale.norms_source {
+                let base = path.parent().unwrap_or(Path::new(""));
+                scale.norms = Some(NormTable::load(file, by.clone(), base)?);
+            }
+            ast.push(Entry::Scale(scale));
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("let")
+            && rest.starts_with(char::is_whitespace)
+        {
+            let computed = Computed::parse(rest).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            ast.push(Entry::Let(computed));
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("missing")
+            && rest.starts_with(char::is_whitespace)
+        {
+            let code = MissingCode::parse(rest).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            if code.value.is_none() {
+                let msg = format!("`missing {}` needs a code, e.g. `missing {} >> -99`", code.kind.name(), code.kind.name());
+                return Err(io::Error::new(io::ErrorKind::InvalidData, msg));
+            }
+            ast.push(Entry::Missing(code));
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("mode")
+            && (rest.is_empty() || rest.starts_with(char::is_whitespace))
+        {
+            let quiz = Quiz::parse(rest).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            ast.push(Entry::Quiz(quiz));
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("progress")
+            && (rest.is_empty() || rest.starts_with(char::is_whitespace))
+        {
+            let nav = Navigation::parse("progress", rest).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            ast.push(Entry::Navigation(nav));
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("toc")
+            && (rest.is_empty() || rest.starts_with(char::is_whitespace))
+        {
+            let nav = Navigation::parse("toc", rest).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            ast.push(Entry::Navigation(nav));
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("navigation")
+            && rest.starts_with(char::is_whitespace)
+        {
+            let nav = Navigation::parse("navigation", rest).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            ast.push(Entry::Navigation(nav));
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("bank")
+            && rest.starts_with(char::is_whitespace)
+        {
+            let (name, block) = match rest.find('{') {
+                Some(open_pos) => (&rest[..open_pos], read_brace_block(&mut lines_iter, &rest[open_pos + 1..])?),
+                None => (rest, read_brace_block(&mut lines_iter, "")?),
+            };
+            let bank = Bank::parse(name, &block).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            ast.push(Entry::Bank(bank));
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("timer")
+            && rest.starts_with(char::is_whitespace)
+        {
+            time_limit = Some(parse_time_limit(rest).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?);
+            if current_page.is_none() {
+                current_page = Some(("untitled".to_string(), Vec::new()));
+            }
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("draw")
+            && rest.starts_with(char::is_whitespace)
+        {
+            let draw = Draw::parse(rest).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            if let Some((_title, content)) = current_page.as_mut() {
+                content.push(Question::Draw(Box::new(draw)));
+            } else {
+                current_page = Some(("untitled".to_string(), vec![Question::Draw(Box::new(draw))]));
+            }
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("submit")
+            && (rest.is_empty() || rest.starts_with(char::is_whitespace))
+        {
+            // `submit "url"` optionally followed by a `{ ... }` block of options
+            let submit = match rest.find('{') {
+                Some(open_pos) => {
+                    let block = read_brace_block(&mut lines_iter, &rest[open_pos + 1..])?;
+                    Submit::parse(&rest[..open_pos], Some(&block))
+                }
+                None => Submit::parse(rest, None),
+            }
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            ast.push(Entry::Submit(submit));
+            continue;
+        }
+
         if line.starts_with("import") {
             let path = line.split('"').nth(1).unwrap_or("").to_string();
             ast.push(Entry::Import { path });
@@ -294,12 +433,16 @@ pub fn compile<P: AsRef<Path>>(path: P) -> io::Result<Vec<Entry>> {
             };
 
             let choose_node = Choose::parse(&block, id);
+            if let Err(e) = choose_node.check() {
+                let name = choose_node.id.as_deref().unwrap_or(&choose_node.question);
+                return Err(io::Error::new(io::ErrorKind::InvalidData, format!("choice {}: {}", name, e)));
+            }
 
             if let Some((_title, content)) = current_page.as_mut() {
-                content.push(Question::Choose(choose_node));
+                content.push(Question::Choose(Box::new(choose_node)));
             } else {
                 // Start a new current page when none exists.
-                current_page = Some(("untitled".to_string(), vec![Question::Choose(choose_node)]));
+                current_page = Some(("untitled".to_string(), vec![Question::Choose(Box::new(choose_node))]));
             }
 
             continue;
@@ -360,8 +503,206 @@ pub fn compile<P: AsRef<Path>>(path: P) -> io::Result<Vec<Entry>> {
     }
 
     if let Some((title, content)) = current_page.take() {
-        ast.push(Entry::Page { title, content });
+        ast.push(Entry::Page { title, time_limit, content });
     }
 
+    resolve_draws(path, &mut ast, importing)?;
+    resolve_missing(&mut ast).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    // computed variables and conditions: defined references, no cycles, well typed
+    check_expressions(&ast).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
     Ok(ast)
 }
+
+/// Fill every `draw` with the matching questions of its bank. Banks come from the
+/// document itself or, when not declared there, from the files it imports (and the
+/// files those import). A bank question may be in the pool of one draw only.
+fn resolve_draws(path: &Path, ast: &mut [Entry], importing: &mut Vec<PathBuf>) -> io::Result<()> {
+    let invalid = |e: String| io::Error::new(io::ErrorKind::InvalidData, e);
+    let mut banks: Vec<Bank> = Vec::new();
+    for entry in ast.iter() {
+        if let Entry::Bank(bank) = entry {
+            if banks.iter().any(|b| b.name == bank.name) {
+                return Err(invalid(format!("bank `{}` is declared twice", bank.name)));
+            }
+            banks.push(bank.clone());
+        }
+    }
+    let wanted = |ast: &[Entry], banks: &[Bank]| {
+        ast.iter().any(|e| match e {
+            Entry::Page { content, .. } => content
+                .iter()
+                .any(|q| matches!(q, Question::Draw(d) if !banks.iter().any(|b| b.name == d.bank))),
+            _ => false,
+        })
+    };
+    // imports are only read when a draw needs a bank the document does not declare
+    if wanted(ast, &banks) {
+        importing.push(path.to_path_buf());
+        let imported = imported_banks(path, ast, importing);
+        importing.pop();
+        banks.extend(imported?.into_iter().filter(|b| !banks.iter().any(|d| d.name == b.name)).collect::<Vec<_>>());
+    }
+
+    let mut pooled: Vec<(String, String)> = Vec::new();
+    for entry in ast.iter_mut() {
+        let Entry::Page { content, .. } = entry else { continue };
+        for q in content.iter_mut() {
+            let Question::Draw(draw) = q else { continue };
+            let bank = banks.iter().find(|b| b.name == draw.bank).ok_or_else(|| {
+                invalid(format!("draw: no bank `{}` (declare it here or in an imported file)", draw.bank))
+            })?;
+            draw.resolve(bank).map_err(|e| invalid(format!("draw {}: {}", draw.key, e)))?;
+            if pooled.iter().any(|(_, k)| *k == draw.key) {
+                return Err(invalid(format!("draw {}: the key is used by another draw (name it with `as <key>`)", draw.key)));
+            }
+            for key in draw.item_keys() {
+                if let Some((other, _)) = pooled.iter().find(|(_, k)| k == key) {
+                    return Err(invalid(format!("draw {}: question `{}` is also in the pool of draw {}", draw.key, key, other)));
+                }
+                pooled.push((draw.key.clone(), key.to_string()));
+            }
+            pooled.push((draw.key.clone(), draw.key.clone()));
+        }
+    }
+    Ok(())
+}
+
+/// Banks declared in the files `ast` imports, depth first; files already being
+/// resolved are skipped.
+fn imported_banks(path: &Path, ast: &[Entry], importing: &mut Vec<PathBuf>) -> io::Result<Vec<Bank>> {
+    let base = path.parent().unwrap_or(Path::new(""));
+    let mut banks = Vec::new();
+    for entry in ast {
+        let Entry::Import { path: import } = entry else { continue };
+        let file = base.join(import);
+        if import.is_empty() || importing.contains(&file) {
+            continue;
+        }
+        let imported = compile_importing(&file, importing)
+            .map_err(|e| io::Error::new(e.kind(), format!("import \"{}\": {}", import, e)))?;
+        importing.push(file.clone());
+        let nested = imported_banks(&file, &imported, importing);
+        importing.pop();
+        banks.extend(imported.into_iter().filter_map(|e| match e {
+            Entry::Bank(bank) => Some(bank),
+            _ => None,
+        }));
+        banks.extend(nested?);
+    }
+    Ok(banks)
+}
+
+/// Give every choice question its missing-value codes, merging the document-level
+/// `missing` declarations (wherever they appear) into its own `.missing` lines.
+fn resolve_missing(ast: &mut [Entry]) -> Result<(), String> {
+    let document: Vec<MissingCode> = ast
+        .iter()
+        .filter_map(|e| match e {
+            Entry::Missing(code) => Some(code.clone()),
+            _ => None,
+        })
+        .collect();
+    let document: Vec<&MissingCode> = document.iter().collect();
+    for entry in ast.iter_mut() {
+        if let Entry::Page { content, .. } = entry {
+            let choices = content.iter_mut().flat_map(|q| match q {
+                Question::Choose(c) => vec![c.as_mut()],
+                Question::Draw(d) => d.items.iter_mut().collect(),
+                _ => Vec::new(),
+            });
+            for c in choices {
+                c.resolve_missing(&document).map_err(|e| {
+                    format!("choice {}: {}", c.id.as_deref().unwrap_or(&c.question), e)
+                })?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The `import` paths and the `norms` tables of the scales named in one file. This is
+/// a light scan rather than [`compile`], so a file with semantic errors (a bad draw, a
+/// mixed-type choice, ...) still reports what it depends on; unreadable files give nothing.
+fn referenced_files(path: &Path) -> (Vec<String>, Vec<String>) {
+    let (mut imports, mut norms) = (Vec::new(), Vec::new());
+    let Ok(file) = File::open(path) else {
+        return (imports, norms);
+    };
+    let mut lines = io::BufReader::new(file).lines().peekable();
+
+    while let Some(Ok(raw)) = lines.next() {
+        let line = raw.trim();
+        if line.starts_with("//") || line.starts_with("title") || line.starts_with("@p") {
+            continue;
+        }
+        if line.starts_with("import") {
+            imports.push(line.split('"').nth(1).unwrap_or("").to_string());
+        } else if let Some(rest) = line.strip_prefix("scale")
+            && rest.starts_with(char::is_whitespace)
+        {
+            let (name, after) = match rest.find('{') {
+                Some(open_pos) => (&rest[..open_pos], &rest[open_pos + 1..]),
+                None => (rest, ""),
+            };
+            let Ok(block) = read_brace_block(&mut lines, after) else { break };
+            if let Some((file, _)) = Scale::parse(name, &block).norms_source {
+                norms.push(file);
+            }
+        } else if let Some(open_pos) = raw.find('{') {
+            // skip other blocks so that their contents are not read as directives
+            if read_brace_block(&mut lines, &raw[open_pos + 1..]).is_err() {
+                break;
+            }
+        }
+    }
+    (imports, norms)
+}
+
+/// All source files a questionnaire depends on: the input itself followed by every file
+/// reachable through `import "..."` and the norm tables named by `norms` in a scale
+/// (both resolved relative to the file that names them).
+/// Files that fail to compile or do not exist are still listed so callers can watch them.
+pub fn source_files<P: AsRef<Path>>(path: P) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = Vec::new();
+    let mut norm_files: Vec<PathBuf> = Vec::new();
+    let mut pending: Vec<PathBuf> = vec![path.as_ref().to_path_buf()];
+
+    while let Some(file) = pending.pop() {
+        if files.contains(&file) {
+            continue;
+        }
+        let base = file.parent().map(Path::to_path_buf).unwrap_or_default();
+        let (imports, norms) = referenced_files(&file);
+        pending.extend(imports.iter().rev().filter(|p| !p.is_empty()).map(|p| base.join(p)));
+        norm_files.extend(norms.iter().map(|n| base.join(n)));
+        files.push(file);
+    }
+
+    for file in norm_files {
+        if !files.contains(&file) {
+            files.push(file);
+        }
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn source_files_survive_compile_errors() {
+        let dir = std::env::temp_dir().join(format!("sqe-source-files-{}", std::process::id()));
+        fs::create_dir_all(dir.join("norms")).unwrap();
+        let main = dir.join("main.sqe");
+        fs::write(&main, "title T\nimport \"bank.sqe\"\nscale s {\n  a\n  norms \"norms/s.csv\" by age\n}\n@p A\ndraw 1 from pool\n").unwrap();
+        // mixed option types make the import fail to compile
+        fs::write(dir.join("bank.sqe"), "bank pool {\nchoice a {\nA?\nYes >> 1\nNo >> no\n}\n}\n").unwrap();
+        assert!(compile(&main).is_err());
+        assert_eq!(source_files(&main), vec![main.clone(), dir.join("bank.sqe"), dir.join("norms/s.csv")]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
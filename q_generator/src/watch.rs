@@ -0,0 +1,71 @@
+// === src/watch.rs ===
+
+//! Polling file watcher. Modification times are compared on every tick, which keeps the
+//! tool dependency-free and behaves the same on every platform and editor (including
+//! editors that save by renaming a temp file over the original).
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::transcompiler;
+
+/// How often watched files are checked for changes.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(300);
+
+#[derive(Debug, Default)]
+pub struct Watcher {
+    stamps: HashMap<PathBuf, Option<(SystemTime, u64)>>,
+}
+
+fn stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let meta = fs::metadata(path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
+
+impl Watcher {
+    pub fn new(files: &[PathBuf]) -> Self {
+        let mut w = Watcher::default();
+        w.changed(files);
+        w
+    }
+
+    /// Record the current state of `files` and report whether anything differs from the
+    /// previous call: a file was modified, created, deleted, or the set of files changed.
+    pub fn changed(&mut self, files: &[PathBuf]) -> bool {
+        let current: HashMap<PathBuf, Option<(SystemTime, u64)>> =
+            files.iter().map(|f| (f.clone(), stamp(f))).collect();
+        let changed = current != self.stamps;
+        self.stamps = current;
+        changed
+    }
+}
+
+/// Poll the questionnaire at `input` and all of its imports forever, calling `on_change`
+/// after every modification. The set of imports is re-resolved after each change and
+/// `on_files` gets the new set whenever it differs.
+pub fn watch_sources<F, G>(input: &Path, mut on_change: F, mut on_files: G) -> !
+where
+    F: FnMut(),
+    G: FnMut(&[PathBuf]),
+{
+    let mut files = transcompiler::source_files(input);
+    let mut watcher = Watcher::new(&files);
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        if !watcher.changed(&files) {
+            continue;
+        }
+        on_change();
+
+        // imports may have been added or removed by the edit
+        let updated = transcompiler::source_files(input);
+        if updated != files {
+            files = updated;
+            watcher.changed(&files);
+            on_files(&files);
+        }
+    }
+}
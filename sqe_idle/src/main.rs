@@ -343,7 +343,7 @@ fn run_sqe_core(code: String, sender_html: glib::Sender<Option<String>>, term_se
     let tmp_path_cl = tmp_path.clone();
     thread::spawn(move || {
         match Command::new("sqe-core")
-            .arg("--input").arg(&sqe_file)
+            .arg("build").arg(&sqe_file)
             .arg("--output").arg(&tmp_path_cl)
             .output()
         {